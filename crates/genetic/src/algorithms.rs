@@ -1,27 +1,20 @@
-use rand::{thread_rng, Rng};
+use rand::{rngs::StdRng, Rng};
 
 /// A trait with only associated functions is (more or less) just a namespace and should probably be a mod.
 /// Converted crossover to a method.
 ///
-/// Defining it as a method allows us to call crossover on any Vec<f32> instance (if the trait is in scope).
+/// Defining it as a method allows us to call crossover on any `Vec<f32>` instance (if the trait is in scope).
 pub trait GeneticCrossover {
-    fn crossover(&self, b: &Vec<f32>, mutation_rate: f64) -> Vec<f32>;
+    fn crossover(&self, b: &[f32], rng: &mut StdRng) -> Vec<f32>;
 }
 
-impl GeneticCrossover for Vec<f32> {
-    fn crossover(&self, b: &Vec<f32>, mutation_rate: f64) -> Vec<f32> {
-        let a = self;
-        let mut c: Vec<f32> = Vec::with_capacity(a.capacity());
-        for i in 0..a.len() {
-            let gene_mutation_occurred = thread_rng().gen_bool(mutation_rate);
-            let x = if gene_mutation_occurred {
-                thread_rng().gen_range(-3.0..3.0)
-            } else {
-                a[i].crossover(b[i], u32::create_bit_mask(2))
-            };
-            c.push(x);
-        }
-        c
+/// Splices every gene of both parents bitwise. Mutation happens afterwards, see [`crate::mutation`].
+impl GeneticCrossover for [f32] {
+    fn crossover(&self, b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        self.iter()
+            .zip(b)
+            .map(|(a, b)| a.crossover(*b, u32::create_bit_mask(2, rng)))
+            .collect()
     }
 }
 
@@ -42,7 +35,7 @@ impl NumericCrossover<f32> for f32 {
 }
 
 trait BitMask {
-    fn create_bit_mask(intersections: u8) -> u32;
+    fn create_bit_mask(intersections: u8, rng: &mut StdRng) -> u32;
 }
 
 impl BitMask for u32 {
@@ -59,12 +52,12 @@ impl BitMask for u32 {
      *                          |              |
      *                          intersection   intersection
      */
-    fn create_bit_mask(intersections: u8) -> u32 {
+    fn create_bit_mask(intersections: u8, rng: &mut StdRng) -> u32 {
         let mut remaining_capacity = 32;
         let mut partitions: Vec<u8> = vec![0; (intersections).into()]
             .iter()
             .map(|_| {
-                let result = thread_rng().gen_range(0..remaining_capacity);
+                let result = rng.gen_range(0..remaining_capacity);
                 remaining_capacity -= result;
                 result
             })
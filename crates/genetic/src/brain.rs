@@ -0,0 +1,49 @@
+use std::io;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+
+use crate::genome::Topology;
+use crate::organism::Reproduction;
+use crate::persistence::{self, Persist};
+
+/// The evolving neural network that decides what an organism does.
+///
+/// [`crate::genome::Genome`] evolves the weights of a fixed stack of layers,
+/// [`crate::neat::Neat`] also grows its topology.
+pub trait Brain: Clone + Send + Sync + Persist {
+    /// Identifies files that store a single brain of this kind
+    const MAGIC: &'static [u8; 4];
+    const VERSION: u32;
+
+    /// Creates a random network with `inputs` inputs and `outputs` outputs, shaped by `topology`.
+    fn random(inputs: usize, outputs: usize, topology: &Topology, rng: &mut StdRng) -> Self;
+
+    /// Feeds `input` through the network and returns the activations of the outputs.
+    fn forward(&self, input: &[f32]) -> Vec<f32>;
+
+    /// Creates the mutated child of two parents.
+    fn crossover(&self, b: &Self, reproduction: &Reproduction, rng: &mut StdRng) -> Self;
+
+    /// How different two networks are, 0 if they are identical
+    fn distance(&self, other: &Self) -> f32;
+
+    /// The mutation rate the network was mutated with and passes on to its children, 0 if it never carried one
+    fn mutation_rate(&self) -> f64;
+
+    /// Checks that a loaded network fits the given inputs, outputs and topology, the error describes the mismatch.
+    fn check_shape(&self, inputs: usize, outputs: usize, topology: &Topology)
+        -> Result<(), String>;
+
+    /// Saves the network on its own, so it can be played without the population it came from.
+    fn save(&self, path: &Path) -> io::Result<()> {
+        persistence::save_file(path, Self::MAGIC, Self::VERSION, |writer| {
+            self.write_to(writer)
+        })
+    }
+
+    fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = persistence::open_file(path, Self::MAGIC, Self::VERSION)?;
+        Self::read_from(&mut reader)
+    }
+}
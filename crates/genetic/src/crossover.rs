@@ -0,0 +1,226 @@
+use std::fmt::Debug;
+
+use rand::{rngs::StdRng, seq::index, Rng};
+
+use crate::algorithms::GeneticCrossover;
+
+/// Combines the genes of two parents into the genes of a child.
+///
+/// Genes are handed over one segment at a time, e.g. all weights and biases of one layer.
+/// Both segments always have the same length.
+pub trait CrossoverOperator: Debug + Send + Sync {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32>;
+}
+
+/// Splices the IEEE-754 bit patterns of both genes with a random two point mask.
+///
+/// It is cheap, but can produce huge exponents, infinities and NaN.
+#[derive(Debug)]
+pub struct BitSplice;
+
+impl CrossoverOperator for BitSplice {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        a.crossover(b, rng)
+    }
+}
+
+/// Takes every gene from either parent with equal probability.
+#[derive(Debug)]
+pub struct Uniform;
+
+impl CrossoverOperator for Uniform {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| if rng.gen_bool(0.5) { *a } else { *b })
+            .collect()
+    }
+}
+
+/// Cuts both parents at `points` random gene indices and takes the pieces alternately, starting with a random parent.
+///
+/// One point gives the classic single-point crossover.
+#[derive(Debug)]
+pub struct Points {
+    pub points: usize,
+}
+
+impl CrossoverOperator for Points {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        let cut_count = self.points.min(a.len().saturating_sub(1));
+        let mut cuts = index::sample(rng, a.len().saturating_sub(1), cut_count).into_vec();
+        cuts.sort_unstable();
+
+        let mut from_a = rng.gen_bool(0.5);
+        let mut next_cut = cuts.iter().map(|cut| cut + 1).peekable();
+        (0..a.len())
+            .map(|i| {
+                if next_cut.peek() == Some(&i) {
+                    next_cut.next();
+                    from_a = !from_a;
+                }
+                if from_a {
+                    a[i]
+                } else {
+                    b[i]
+                }
+            })
+            .collect()
+    }
+}
+
+/// BLX-alpha: a uniform value from the range spanned by both genes, widened by `alpha` times its width on each side.
+#[derive(Debug)]
+pub struct Blend {
+    pub alpha: f32,
+}
+
+impl CrossoverOperator for Blend {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| {
+                let (low, high) = (a.min(*b), a.max(*b));
+                let width = high - low;
+                let extension = self.alpha * width;
+                let (start, end) = (low - extension, high + extension);
+                // rand panics on ranges wider than the largest float, huge genes fall back to the range between the parents
+                if !(width > 0.0 && width.is_finite()) {
+                    *a
+                } else if (end - start).is_finite() {
+                    rng.gen_range(start..=end)
+                } else {
+                    rng.gen_range(low..=high)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Simulated binary crossover: children close to the parents are likely, the larger `eta` the closer.
+#[derive(Debug)]
+pub struct Sbx {
+    pub eta: f32,
+}
+
+impl CrossoverOperator for Sbx {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        let exponent = 1.0 / (self.eta + 1.0);
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| {
+                let u: f32 = rng.gen();
+                let beta = if u <= 0.5 {
+                    (2.0 * u).powf(exponent)
+                } else {
+                    (1.0 / (2.0 * (1.0 - u))).powf(exponent)
+                };
+                // SBX creates two children symmetric around the mean of the parents, we keep one of them
+                let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                0.5 * ((a + b) + sign * beta * (a - b))
+            })
+            .collect()
+    }
+}
+
+/// Takes the whole segment from either parent, so layers are inherited as a unit.
+#[derive(Debug)]
+pub struct LayerSwap;
+
+impl CrossoverOperator for LayerSwap {
+    fn crossover(&self, a: &[f32], b: &[f32], rng: &mut StdRng) -> Vec<f32> {
+        if rng.gen_bool(0.5) {
+            a.to_vec()
+        } else {
+            b.to_vec()
+        }
+    }
+}
+
+/// What happens to genes of a child that are not finite after crossover and mutation.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum GeneGuard {
+    /// Non-finite genes are replaced by the gene of the first parent
+    #[default]
+    Reject,
+    /// Every gene is clamped into `-limit..=limit` and NaN becomes 0, this also tames huge exponents
+    Repair { limit: f32 },
+}
+
+impl GeneGuard {
+    /// Returns a usable value for `gene`, `parent_gene` is the same gene of the first parent.
+    pub fn apply(self, gene: f32, parent_gene: f32) -> f32 {
+        match self {
+            GeneGuard::Reject if gene.is_finite() => gene,
+            GeneGuard::Reject if parent_gene.is_finite() => parent_gene,
+            GeneGuard::Reject => 0.0,
+            GeneGuard::Repair { .. } if gene.is_nan() => 0.0,
+            GeneGuard::Repair { limit } => gene.clamp(-limit, limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const A: [f32; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    const B: [f32; 6] = [-1.0, -2.0, -3.0, -4.0, -5.0, -6.0];
+
+    #[test]
+    fn one_point_joins_a_prefix_and_a_suffix() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let child = Points { points: 1 }.crossover(&A, &B, &mut rng);
+            let (first, second) = if child[0] == A[0] { (A, B) } else { (B, A) };
+            let cut = child.iter().zip(first).take_while(|(c, f)| *c == f).count();
+            assert!((1..A.len()).contains(&cut));
+            assert_eq!(child[cut..], second[cut..]);
+        }
+    }
+
+    #[test]
+    fn uniform_and_layer_take_every_gene_from_a_parent() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let child = Uniform.crossover(&A, &B, &mut rng);
+        assert!((0..A.len()).all(|i| child[i] == A[i] || child[i] == B[i]));
+        let child = LayerSwap.crossover(&A, &B, &mut rng);
+        assert!(child == A || child == B);
+    }
+
+    #[test]
+    fn blend_without_alpha_stays_between_the_parents() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let child = Blend { alpha: 0.0 }.crossover(&A, &B, &mut rng);
+        assert!((0..A.len()).all(|i| (B[i]..=A[i]).contains(&child[i])));
+    }
+
+    #[test]
+    fn sbx_keeps_genes_both_parents_share() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let child = Sbx { eta: 2.0 }.crossover(&A, &A, &mut rng);
+        assert_eq!(child, A);
+    }
+
+    #[test]
+    fn guards_fix_genes_that_are_not_finite() {
+        assert_eq!(GeneGuard::Reject.apply(f32::NAN, 0.5), 0.5);
+        assert_eq!(GeneGuard::Reject.apply(f32::INFINITY, f32::NAN), 0.0);
+        assert_eq!(GeneGuard::Reject.apply(2.0, 0.5), 2.0);
+        let repair = GeneGuard::Repair { limit: 3.0 };
+        assert_eq!(repair.apply(f32::NAN, 0.5), 0.0);
+        assert_eq!(repair.apply(f32::NEG_INFINITY, 0.5), -3.0);
+        assert_eq!(repair.apply(2.0, 0.5), 2.0);
+    }
+
+    #[test]
+    fn blend_of_huge_genes_stays_finite() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let blend = Blend { alpha: 1e38 };
+        let child = blend.crossover(&[1e38, -3e38, 1.0], &[2e38, 3e38, 2.0], &mut rng);
+        assert!(child.iter().all(|gene| gene.is_finite()));
+        assert!((1e38..=2e38).contains(&child[0]));
+    }
+}
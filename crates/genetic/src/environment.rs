@@ -0,0 +1,53 @@
+use crate::brain::Brain;
+
+/// What happened in a single step of an [`Environment`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Step {
+    /// How much the step improved the result of the agent, negative if it made it worse
+    pub reward: f32,
+    /// The episode is over, the environment has to be reset before the next step
+    pub done: bool,
+}
+
+/// A world an agent acts in, e.g. a game.
+///
+/// The environment knows nothing about who decides the actions, so the same one can be played by a network or a human.
+pub trait Environment {
+    type Action;
+
+    /// Starts a new episode.
+    fn reset(&mut self);
+
+    /// What the agent perceives of the current state, the input of its network
+    fn observe(&self) -> Vec<f32>;
+
+    /// Carries out `action`.
+    fn step(&mut self, action: Self::Action) -> Step;
+}
+
+/// An environment with a fixed set of actions, an agent picks one per step.
+pub trait DiscreteActions: Environment<Action: Copy + 'static> {
+    /// Every action the agent can pick, a network has one output per action
+    const ACTIONS: &'static [Self::Action];
+}
+
+/// Decides the actions of an agent in the environment `E`.
+///
+/// A policy sees the whole environment, a network only looks at [`Environment::observe`],
+/// while e.g. a human player keeps going in the direction the game shows.
+pub trait Policy<E: Environment> {
+    /// Picks the next action in the current state of `environment`.
+    fn act(&self, environment: &E) -> E::Action;
+}
+
+/// A network picks the action of its strongest output, so every brain can play every game with discrete actions.
+impl<B: Brain, E: DiscreteActions> Policy<E> for B {
+    fn act(&self, environment: &E) -> E::Action {
+        self.forward(&environment.observe())
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| E::ACTIONS[index])
+            .expect("The network has no outputs")
+    }
+}
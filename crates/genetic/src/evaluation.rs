@@ -0,0 +1,61 @@
+/// How the fitness of an organism is measured.
+///
+/// A single episode is mostly luck (where the apples land), so an organism can play several and the scores are combined.
+#[derive(Clone, Debug)]
+pub struct Evaluation {
+    /// Number of episodes every organism plays per generation
+    pub episodes: usize,
+    /// Seeds the episodes the same way for every organism and generation, so all of them face the same games
+    ///
+    /// Without it every organism continues its own random number stream.
+    pub shared_seed: Option<u64>,
+    pub aggregation: Aggregation,
+}
+
+impl Default for Evaluation {
+    fn default() -> Self {
+        Evaluation {
+            episodes: 1,
+            shared_seed: None,
+            aggregation: Aggregation::Mean,
+        }
+    }
+}
+
+impl Evaluation {
+    /// The seed of the given episode, if the episodes are shared
+    pub fn episode_seed(&self, episode: usize) -> Option<u64> {
+        self.shared_seed
+            .map(|seed| seed.wrapping_add(episode as u64))
+    }
+}
+
+/// Combines the scores of several episodes into one fitness.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Aggregation {
+    Mean,
+    Median,
+    /// The worst episode, rewards organisms that never fail
+    Min,
+}
+
+impl Aggregation {
+    pub fn aggregate(self, mut scores: Vec<f32>) -> f32 {
+        if scores.is_empty() {
+            return 0.0;
+        }
+        match self {
+            Aggregation::Mean => scores.iter().sum::<f32>() / scores.len() as f32,
+            Aggregation::Median => {
+                scores.sort_by(f32::total_cmp);
+                let middle = scores.len() / 2;
+                if scores.len().is_multiple_of(2) {
+                    (scores[middle - 1] + scores[middle]) / 2.0
+                } else {
+                    scores[middle]
+                }
+            }
+            Aggregation::Min => scores.iter().copied().fold(f32::INFINITY, f32::min),
+        }
+    }
+}
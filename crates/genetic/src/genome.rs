@@ -0,0 +1,409 @@
+use std::io::{self, Read, Write};
+
+use rand::{rngs::StdRng, Rng};
+
+use crate::brain::Brain;
+use crate::organism::Reproduction;
+use crate::persistence::{invalid_data, Persist};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Activation {
+    None,
+    ReLU,
+    Tanh,
+    Sigmoid,
+}
+
+impl Activation {
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::None => x,
+            Activation::ReLU => x.max(0.0),
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Activation::None => "none",
+            Activation::ReLU => "relu",
+            Activation::Tanh => "tanh",
+            Activation::Sigmoid => "sigmoid",
+        }
+    }
+}
+
+/// The shape of the network between its inputs and outputs.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Topology {
+    /// Size and activation of every hidden layer
+    pub hidden: Vec<(usize, Activation)>,
+    pub output_activation: Activation,
+}
+
+impl Default for Topology {
+    /// One hidden layer of 4 ReLU neurons
+    fn default() -> Self {
+        Topology {
+            hidden: vec![(4, Activation::ReLU)],
+            output_activation: Activation::None,
+        }
+    }
+}
+
+impl Topology {
+    /// The (inputs, outputs, activation) of every layer of a network with this topology
+    pub fn layers(&self, inputs: usize, outputs: usize) -> Vec<(usize, usize, Activation)> {
+        self.hidden
+            .iter()
+            .copied()
+            .chain(std::iter::once((outputs, self.output_activation)))
+            .scan(inputs, |layer_inputs, (size, activation)| {
+                let layer = (*layer_inputs, size, activation);
+                *layer_inputs = size;
+                Some(layer)
+            })
+            .collect()
+    }
+}
+
+/// A fully connected layer.
+///
+/// `weights` holds one row of `inputs` values per output, like the dfdx `Linear` this replaced.
+/// dfdx needs the layer sizes at compile time, but the number of inputs now depends on the chosen sensors.
+#[derive(Clone)]
+pub struct Layer {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub activation: Activation,
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+    /// Mutation step size of every weight followed by every bias, 0 until a self-adaptive operator sets it
+    pub step_sizes: Vec<f32>,
+}
+
+impl Layer {
+    /// Initializes the layer uniformly in `-1/sqrt(inputs)..1/sqrt(inputs)`, the same way dfdx did.
+    fn random(inputs: usize, outputs: usize, activation: Activation, rng: &mut StdRng) -> Self {
+        let bound = 1.0 / (inputs as f32).sqrt();
+        Layer {
+            inputs,
+            outputs,
+            activation,
+            weights: (0..inputs * outputs)
+                .map(|_| rng.gen_range(-bound..bound))
+                .collect(),
+            biases: (0..outputs).map(|_| rng.gen_range(-bound..bound)).collect(),
+            step_sizes: vec![0.0; inputs * outputs + outputs],
+        }
+    }
+
+    /// The weights followed by the biases, the segment a [`crate::crossover::CrossoverOperator`] works on
+    fn genes(&self) -> Vec<f32> {
+        [self.weights.as_slice(), self.biases.as_slice()].concat()
+    }
+
+    /// Mutates every weight and bias with the probability `mutation_rate`.
+    fn mutate(&mut self, mutation_rate: f64, reproduction: &Reproduction, rng: &mut StdRng) {
+        let genes = self.weights.iter_mut().chain(self.biases.iter_mut());
+        for (gene, step_size) in genes.zip(self.step_sizes.iter_mut()) {
+            if rng.gen_bool(mutation_rate) {
+                *gene = reproduction.mutation.mutate(*gene, step_size, rng);
+            }
+        }
+    }
+
+    /// Applies the gene guard to every weight and bias, `parent` provides the replacements for rejected genes.
+    fn guard(&mut self, reproduction: &Reproduction, parent: &Layer) {
+        let genes = self.weights.iter_mut().chain(self.biases.iter_mut());
+        let parent_genes = parent.weights.iter().chain(&parent.biases);
+        for (gene, parent_gene) in genes.zip(parent_genes) {
+            *gene = reproduction.guard.apply(*gene, *parent_gene);
+        }
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks_exact(self.inputs)
+            .zip(&self.biases)
+            .map(|(row, bias)| {
+                let sum: f32 = row.iter().zip(input).map(|(w, x)| w * x).sum();
+                self.activation.apply(sum + bias)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+pub struct Genome {
+    pub layers: Vec<Layer>,
+    /// The rate the genome was mutated with, see [`Reproduction::child_mutation_rate`]
+    pub mutation_rate: f64,
+}
+
+impl Genome {
+    /// The (inputs, outputs, activation) of every layer, comparable to [`Topology::layers`]
+    pub fn layer_shapes(&self) -> Vec<(usize, usize, Activation)> {
+        self.layers
+            .iter()
+            .map(|layer| (layer.inputs, layer.outputs, layer.activation))
+            .collect()
+    }
+}
+
+impl Brain for Genome {
+    const MAGIC: &'static [u8; 4] = b"SNKG";
+    /// Version 2 added the mutation rate
+    const VERSION: u32 = 2;
+
+    /// Creates a genome with random weights for a network with `inputs` inputs and `outputs` outputs.
+    fn random(inputs: usize, outputs: usize, topology: &Topology, rng: &mut StdRng) -> Self {
+        let layers = topology
+            .layers(inputs, outputs)
+            .into_iter()
+            .map(|(inputs, outputs, activation)| Layer::random(inputs, outputs, activation, rng))
+            .collect();
+        Genome {
+            layers,
+            mutation_rate: 0.0,
+        }
+    }
+
+    /// Feeds `input` through the network and returns the activations of the output layer.
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.layers
+            .iter()
+            .fold(input.to_vec(), |activations, layer| {
+                layer.forward(&activations)
+            })
+    }
+
+    /// The root mean square difference of all weights and biases, so it does not grow with the size of the network.
+    fn distance(&self, other: &Genome) -> f32 {
+        let (sum, count) = self
+            .layers
+            .iter()
+            .zip(&other.layers)
+            .flat_map(|(a, b)| {
+                let weights = a.weights.iter().zip(&b.weights);
+                weights.chain(a.biases.iter().zip(&b.biases))
+            })
+            .fold((0.0, 0), |(sum, count), (a, b)| {
+                // Bit splicing can produce huge genes, their squares only fit into a f64
+                (sum + (*a as f64 - *b as f64).powi(2), count + 1)
+            });
+        (sum / count.max(1) as f64).sqrt() as f32
+    }
+
+    /**
+     * Used to be four hand written blocks, one per tensor of the dfdx model. Now it is one per layer, so it works for any depth.
+     * Both parents need the same topology.
+     *
+     * Converted associated function to method. A method can still be called like a associated function, but also like a method
+     *
+     * Every layer is one segment for the crossover operator. The child inherits the mean step sizes of its parents,
+     * is mutated afterwards and finally guarded against non-finite genes.
+     */
+    fn crossover(&self, b: &Genome, reproduction: &Reproduction, rng: &mut StdRng) -> Genome {
+        debug_assert_eq!(self.layer_shapes(), b.layer_shapes());
+        let mutation_rate =
+            reproduction.child_mutation_rate(self.mutation_rate, b.mutation_rate, rng);
+        let layers = self
+            .layers
+            .iter()
+            .zip(&b.layers)
+            .map(|(a, b)| {
+                let mut weights = reproduction
+                    .crossover
+                    .crossover(&a.genes(), &b.genes(), rng);
+                let biases = weights.split_off(a.weights.len());
+                let mut child = Layer {
+                    inputs: a.inputs,
+                    outputs: a.outputs,
+                    activation: a.activation,
+                    weights,
+                    biases,
+                    step_sizes: a
+                        .step_sizes
+                        .iter()
+                        .zip(&b.step_sizes)
+                        .map(|(a, b)| inherit_step_size(*a, *b))
+                        .collect(),
+                };
+                child.mutate(mutation_rate, reproduction, rng);
+                child.guard(reproduction, a);
+                child
+            })
+            .collect();
+        Genome {
+            layers,
+            mutation_rate,
+        }
+    }
+
+    fn mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    /// The layers have to match exactly, since the shape of a genome never changes.
+    fn check_shape(
+        &self,
+        inputs: usize,
+        outputs: usize,
+        topology: &Topology,
+    ) -> Result<(), String> {
+        let expected = topology.layers(inputs, outputs);
+        if self.layer_shapes() != expected {
+            return Err(format!(
+                "The saved network is {}, but the chosen sensors and topology need {}",
+                describe_layers(&self.layer_shapes()),
+                describe_layers(&expected)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Formats layers like `6 -> 4 relu -> 4 none`.
+fn describe_layers(layers: &[(usize, usize, Activation)]) -> String {
+    let inputs = layers.first().map_or(0, |(inputs, _, _)| *inputs);
+    layers.iter().fold(
+        inputs.to_string(),
+        |description, (_, outputs, activation)| {
+            format!("{} -> {} {}", description, outputs, activation.name())
+        },
+    )
+}
+
+/// The mean of both step sizes, ignoring one that was never set.
+pub fn inherit_step_size(a: f32, b: f32) -> f32 {
+    match (a > 0.0, b > 0.0) {
+        (true, true) => (a + b) / 2.0,
+        (true, false) => a,
+        _ => b,
+    }
+}
+
+impl Persist for Activation {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let code: u8 = match self {
+            Activation::None => 0,
+            Activation::ReLU => 1,
+            Activation::Tanh => 2,
+            Activation::Sigmoid => 3,
+        };
+        code.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(Activation::None),
+            1 => Ok(Activation::ReLU),
+            2 => Ok(Activation::Tanh),
+            3 => Ok(Activation::Sigmoid),
+            code => Err(invalid_data(format!("Unknown activation {}", code))),
+        }
+    }
+}
+
+/// A layer is stored as its descriptor (inputs, outputs, activation) followed by its weights, biases and step sizes.
+impl Persist for Layer {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        (self.inputs as u32).write_to(writer)?;
+        (self.outputs as u32).write_to(writer)?;
+        self.activation.write_to(writer)?;
+        self.weights.write_to(writer)?;
+        self.biases.write_to(writer)?;
+        self.step_sizes.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let layer = Layer {
+            inputs: u32::read_from(reader)? as usize,
+            outputs: u32::read_from(reader)? as usize,
+            activation: Activation::read_from(reader)?,
+            weights: Vec::read_from(reader)?,
+            biases: Vec::read_from(reader)?,
+            step_sizes: Vec::read_from(reader)?,
+        };
+        if layer.inputs == 0
+            || layer.weights.len() != layer.inputs * layer.outputs
+            || layer.biases.len() != layer.outputs
+            || layer.step_sizes.len() != layer.weights.len() + layer.biases.len()
+        {
+            return Err(invalid_data(format!(
+                "Layer {}x{} has {} weights and {} biases",
+                layer.inputs,
+                layer.outputs,
+                layer.weights.len(),
+                layer.biases.len()
+            )));
+        }
+        Ok(layer)
+    }
+}
+
+/// A genome is stored as its mutation rate and number of layers followed by the layers.
+impl Persist for Genome {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.mutation_rate.write_to(writer)?;
+        (self.layers.len() as u32).write_to(writer)?;
+        self.layers
+            .iter()
+            .try_for_each(|layer| layer.write_to(writer))
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mutation_rate = f64::read_from(reader)?;
+        let count = u32::read_from(reader)?;
+        let layers = (0..count)
+            .map(|_| Layer::read_from(reader))
+            .collect::<io::Result<Vec<Layer>>>()?;
+        if layers.is_empty()
+            || layers
+                .windows(2)
+                .any(|pair| pair[0].outputs != pair[1].inputs)
+        {
+            return Err(invalid_data("The layers of the genome do not fit together"));
+        }
+        if !(0.0..=1.0).contains(&mutation_rate) {
+            return Err(invalid_data(format!(
+                "The mutation rate {} of the genome does not lie between 0 and 1",
+                mutation_rate
+            )));
+        }
+        Ok(Genome {
+            layers,
+            mutation_rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn genome_survives_a_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let topology = Topology {
+            hidden: vec![(5, Activation::Tanh), (3, Activation::ReLU)],
+            output_activation: Activation::Sigmoid,
+        };
+        let mut genome = Genome::random(6, 4, &topology, &mut rng);
+        genome.mutation_rate = 0.02;
+
+        let mut bytes = vec![];
+        genome.write_to(&mut bytes).unwrap();
+        let loaded = Genome::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.layer_shapes(), genome.layer_shapes());
+        assert_eq!(loaded.mutation_rate, genome.mutation_rate);
+        let input = [0.5, -1.0, 2.0, 0.0, 3.0, -0.25];
+        assert_eq!(loaded.forward(&input), genome.forward(&input));
+
+        assert!(Genome::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! A genetic algorithm for any [`organism::Organism`]: the population, its operators and the evolving networks.
+//!
+//! Nothing in here knows about snake or the terminal, the game lives in the `snake` crate.
+
+pub mod algorithms;
+pub mod brain;
+pub mod crossover;
+pub mod environment;
+pub mod evaluation;
+pub mod genome;
+pub mod mutation;
+pub mod mutation_rate;
+pub mod neat;
+pub mod organism;
+pub mod persistence;
+pub mod population;
+pub mod selection;
+pub mod speciation;
+pub mod stats;
+pub mod traits;
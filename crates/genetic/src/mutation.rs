@@ -0,0 +1,127 @@
+use std::fmt::Debug;
+
+use rand::{rngs::StdRng, Rng};
+use rand_distr::StandardNormal;
+
+/// Changes a single gene, the mutation rate decides which genes get mutated.
+pub trait MutationOperator: Debug + Send + Sync {
+    /// Returns the mutated gene.
+    ///
+    /// `step_size` is a strategy parameter stored next to every gene, so self-adaptive operators can evolve it.
+    /// It starts at 0, which means it was never set.
+    fn mutate(&self, gene: f32, step_size: &mut f32, rng: &mut StdRng) -> f32;
+}
+
+/// Replaces the gene with a uniform sample in `-range..range`, forgetting the learned value.
+#[derive(Debug)]
+pub struct UniformReset {
+    pub range: f32,
+}
+
+impl MutationOperator for UniformReset {
+    fn mutate(&self, _gene: f32, _step_size: &mut f32, rng: &mut StdRng) -> f32 {
+        rng.gen_range(-self.range..self.range)
+    }
+}
+
+/// Adds normally distributed noise with a fixed standard deviation.
+#[derive(Debug)]
+pub struct Gaussian {
+    pub sigma: f32,
+}
+
+impl MutationOperator for Gaussian {
+    fn mutate(&self, gene: f32, _step_size: &mut f32, rng: &mut StdRng) -> f32 {
+        gene + self.sigma * rng.sample::<f32, _>(StandardNormal)
+    }
+}
+
+/// Adds normally distributed noise with a standard deviation that every gene evolves on its own.
+///
+/// The step size is mutated first with `sigma' = sigma * exp(learning_rate * N(0, 1))`,
+/// so genes that profit from big steps keep them and others settle down.
+#[derive(Debug)]
+pub struct SelfAdaptiveGaussian {
+    /// Step size of genes that never mutated before
+    pub initial_sigma: f32,
+    pub learning_rate: f32,
+}
+
+impl SelfAdaptiveGaussian {
+    /// Keeps step sizes from collapsing to 0, which would stop the gene from ever changing again
+    const MIN_SIGMA: f32 = 1e-4;
+}
+
+impl MutationOperator for SelfAdaptiveGaussian {
+    fn mutate(&self, gene: f32, step_size: &mut f32, rng: &mut StdRng) -> f32 {
+        if *step_size <= 0.0 {
+            *step_size = self.initial_sigma;
+        }
+        let factor = (self.learning_rate * rng.sample::<f32, _>(StandardNormal)).exp();
+        *step_size = (*step_size * factor).max(Self::MIN_SIGMA);
+        gene + *step_size * rng.sample::<f32, _>(StandardNormal)
+    }
+}
+
+/// Moves the gene by a uniform amount in `-step..step`.
+#[derive(Debug)]
+pub struct Creep {
+    pub step: f32,
+}
+
+impl MutationOperator for Creep {
+    fn mutate(&self, gene: f32, _step_size: &mut f32, rng: &mut StdRng) -> f32 {
+        gene + rng.gen_range(-self.step..self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn uniform_and_creep_stay_in_their_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut step_size = 0.0;
+        for _ in 0..100 {
+            let reset = UniformReset { range: 3.0 }.mutate(10.0, &mut step_size, &mut rng);
+            assert!((-3.0..3.0).contains(&reset));
+            let crept = Creep { step: 0.5 }.mutate(10.0, &mut step_size, &mut rng);
+            assert!((9.5..10.5).contains(&crept));
+        }
+        assert_eq!(step_size, 0.0);
+    }
+
+    #[test]
+    fn gaussian_without_sigma_keeps_the_gene() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut step_size = 0.0;
+        assert_eq!(
+            Gaussian { sigma: 0.0 }.mutate(1.5, &mut step_size, &mut rng),
+            1.5
+        );
+    }
+
+    #[test]
+    fn self_adaptive_step_sizes_start_at_sigma_and_never_vanish() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let first = SelfAdaptiveGaussian {
+            initial_sigma: 0.5,
+            learning_rate: 0.0,
+        };
+        let mut step_size = 0.0;
+        first.mutate(1.0, &mut step_size, &mut rng);
+        assert_eq!(step_size, 0.5);
+
+        let jumpy = SelfAdaptiveGaussian {
+            initial_sigma: 0.5,
+            learning_rate: 100.0,
+        };
+        for _ in 0..100 {
+            jumpy.mutate(1.0, &mut step_size, &mut rng);
+            assert!(step_size >= SelfAdaptiveGaussian::MIN_SIGMA);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng};
+use rand_distr::StandardNormal;
+
+/// What a [`MutationRateController`] gets to know about the generation that just finished.
+pub struct Outcome<'a> {
+    /// The generation that just finished, counted from 0
+    pub generation: usize,
+    /// The mutation rate of the first generation
+    pub initial_rate: f64,
+    pub best_fitness: f32,
+    /// The best fitness of the generation before
+    pub previous_best_fitness: f32,
+    /// The share of children that beat the mean fitness of their parents, `None` if the generation has no children yet
+    pub success_rate: Option<f64>,
+    /// See [`crate::population::Population::diversity`]
+    pub diversity: f32,
+    /// The mutation rates the organisms carry, see [`crate::organism::Organism::mutation_rate`]
+    pub carried_rates: &'a [f64],
+}
+
+/// Decides the mutation rate of the next generation.
+pub trait MutationRateController: Debug + Send + Sync {
+    /// Returns the mutation rate of the next generation, the population clamps it into its bounds.
+    fn adapt(&self, rate: f64, outcome: &Outcome) -> f64;
+
+    /// Returns the mutation rate of a child whose parents carry the rates `a` and `b`, 0 for a parent that never carried one.
+    ///
+    /// The child is mutated with the returned rate and carries it on. By default every child gets the rate of the population.
+    fn inherit(&self, rate: f64, _a: f64, _b: f64, _rng: &mut StdRng) -> f64 {
+        rate
+    }
+}
+
+/// Shrinks the rate by `factor` after a new best fitness and grows it by `factor` otherwise.
+#[derive(Debug)]
+pub struct ProgressStep {
+    pub factor: f64,
+}
+
+impl MutationRateController for ProgressStep {
+    fn adapt(&self, rate: f64, outcome: &Outcome) -> f64 {
+        if outcome.best_fitness > outcome.previous_best_fitness {
+            rate - rate * self.factor
+        } else {
+            rate + rate * self.factor
+        }
+    }
+}
+
+/// Rechenberg's 1/5 success rule: mutate more while more than a fifth of the children beat their parents, less otherwise.
+///
+/// Many successes mean the population climbs a smooth slope and can take bigger steps,
+/// few mean it sits close to an optimum that big steps would jump over.
+#[derive(Debug)]
+pub struct OneFifth {
+    /// Between 0 and 1, the rate is divided by it on success and multiplied by it otherwise
+    pub factor: f64,
+}
+
+impl MutationRateController for OneFifth {
+    fn adapt(&self, rate: f64, outcome: &Outcome) -> f64 {
+        match outcome.success_rate {
+            Some(success_rate) if success_rate > 0.2 => rate / self.factor,
+            Some(success_rate) if success_rate < 0.2 => rate * self.factor,
+            _ => rate,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Decay {
+    /// The rate falls by the same amount every generation
+    Linear,
+    /// The rate falls by the same ratio every generation
+    Exponential,
+}
+
+/// Moves the rate from the initial one to `end` within `generations` generations and keeps it there, whatever happens.
+#[derive(Debug)]
+pub struct Schedule {
+    pub end: f64,
+    pub generations: usize,
+    pub decay: Decay,
+}
+
+impl MutationRateController for Schedule {
+    fn adapt(&self, _rate: f64, outcome: &Outcome) -> f64 {
+        let progress = ((outcome.generation + 1) as f64 / self.generations.max(1) as f64).min(1.0);
+        let start = outcome.initial_rate;
+        match self.decay {
+            Decay::Linear => start + (self.end - start) * progress,
+            Decay::Exponential if start > 0.0 => start * (self.end / start).powf(progress),
+            Decay::Exponential => self.end,
+        }
+    }
+}
+
+/// Multiplies the rate by `factor` whenever the diversity of the population falls below `threshold`, `inner` decides otherwise.
+///
+/// A population that converged on one solution gets shaken up, instead of waiting for the mutation rate to creep up.
+#[derive(Debug)]
+pub struct DiversityBurst {
+    pub threshold: f32,
+    pub factor: f64,
+    pub inner: Arc<dyn MutationRateController>,
+}
+
+impl MutationRateController for DiversityBurst {
+    fn adapt(&self, rate: f64, outcome: &Outcome) -> f64 {
+        if outcome.diversity < self.threshold {
+            rate * self.factor
+        } else {
+            self.inner.adapt(rate, outcome)
+        }
+    }
+
+    fn inherit(&self, rate: f64, a: f64, b: f64, rng: &mut StdRng) -> f64 {
+        self.inner.inherit(rate, a, b, rng)
+    }
+}
+
+/// Every organism carries its own mutation rate, which evolves along with its genes.
+///
+/// A child inherits the mean rate of its parents and changes it with `rate' = rate * exp(learning_rate * N(0, 1))`
+/// before it mutates with it, so the rates that produce good children spread.
+/// The rate of the population is the mean of the carried rates and only starts organisms that never carried one.
+#[derive(Debug)]
+pub struct SelfAdaptive {
+    pub learning_rate: f64,
+}
+
+impl MutationRateController for SelfAdaptive {
+    fn adapt(&self, rate: f64, outcome: &Outcome) -> f64 {
+        let (sum, count) = outcome
+            .carried_rates
+            .iter()
+            .filter(|rate| **rate > 0.0)
+            .fold((0.0, 0), |(sum, count), rate| (sum + rate, count + 1));
+        if count == 0 {
+            rate
+        } else {
+            sum / count as f64
+        }
+    }
+
+    fn inherit(&self, rate: f64, a: f64, b: f64, rng: &mut StdRng) -> f64 {
+        let inherited = match (a > 0.0, b > 0.0) {
+            (true, true) => (a + b) / 2.0,
+            (true, false) => a,
+            (false, true) => b,
+            (false, false) => rate,
+        };
+        inherited * (self.learning_rate * rng.sample::<f64, _>(StandardNormal)).exp()
+    }
+}
@@ -0,0 +1,676 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::brain::Brain;
+use crate::genome::{inherit_step_size, Activation, Topology};
+use crate::organism::Reproduction;
+use crate::persistence::{invalid_data, Persist};
+
+/// Probability of a child to get a new connection between two unconnected neurons
+const ADD_CONNECTION_PROBABILITY: f64 = 0.05;
+/// Probability of a child to get a new neuron in the middle of an existing connection
+const ADD_NODE_PROBABILITY: f64 = 0.03;
+/// A connection disabled in either parent stays disabled in the child with this probability
+const KEEP_DISABLED_PROBABILITY: f64 = 0.75;
+/// Random pairs of neurons tried before the child goes without a new connection
+const ADD_CONNECTION_ATTEMPTS: usize = 20;
+/// Weight of the structural difference in [`Neat::distance`]
+const DISJOINT_COEFFICIENT: f64 = 1.0;
+/// Weight of the mean weight difference of matching connections in [`Neat::distance`]
+const WEIGHT_COEFFICIENT: f64 = 0.4;
+/// Set in the ids of hidden neurons, so they never collide with the inputs, the bias or the outputs
+const HIDDEN_NODE: u64 = 1 << 63;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NodeKind {
+    Input,
+    /// Always 1, so every neuron can learn an offset
+    Bias,
+    Hidden,
+    Output,
+}
+
+#[derive(Clone, Debug)]
+pub struct NodeGene {
+    pub id: u64,
+    pub kind: NodeKind,
+    pub activation: Activation,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConnectionGene {
+    pub from: u64,
+    pub to: u64,
+    pub weight: f32,
+    /// Disabled connections do not take part in the network, but are still inherited
+    pub enabled: bool,
+    /// Mutation step size of the weight, 0 until a self-adaptive operator sets it
+    pub step_size: f32,
+}
+
+impl ConnectionGene {
+    pub fn innovation(&self) -> u64 {
+        innovation(self.from, self.to)
+    }
+}
+
+/// The historical marking of the connection between two neurons.
+///
+/// Classic NEAT hands out innovation numbers from a counter that every genome of a run has to share and checkpoints have to store.
+/// Deriving the number from the neurons it connects gives the same connection the same number in every genome of every run.
+/// Hidden neurons get their ids the same way from the connection they split, see `split_node`.
+pub fn innovation(from: u64, to: u64) -> u64 {
+    mix(mix(from) ^ to)
+}
+
+/// The id of the neuron that splits the connection with the given innovation number
+fn split_node(innovation: u64) -> u64 {
+    mix(innovation) | HIDDEN_NODE
+}
+
+/// The finalizer of splitmix64, scatters similar numbers over the whole range
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// The genes compiled into something that can be evaluated quickly, every tick of every snake runs it.
+#[derive(Clone)]
+struct Network {
+    /// Indices of the hidden and output neurons, every neuron comes after the neurons it depends on
+    order: Vec<usize>,
+    /// The (source index, weight) of the enabled connections into every neuron
+    incoming: Vec<Vec<(usize, f32)>>,
+}
+
+impl Network {
+    /// Sorts the neurons topologically, `None` if the connections contain a cycle or reference a missing neuron.
+    ///
+    /// Disabled connections count as well, since crossover may enable them again.
+    fn compile(nodes: &[NodeGene], connections: &[ConnectionGene]) -> Option<Network> {
+        let index: HashMap<u64, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+        let mut incoming = vec![vec![]; nodes.len()];
+        let mut dependents = vec![vec![]; nodes.len()];
+        let mut pending = vec![0; nodes.len()];
+        for connection in connections {
+            let from = *index.get(&connection.from)?;
+            let to = *index.get(&connection.to)?;
+            if connection.enabled {
+                incoming[to].push((from, connection.weight));
+            }
+            dependents[from].push(to);
+            pending[to] += 1;
+        }
+
+        let mut ready: Vec<usize> = (0..nodes.len())
+            .filter(|node| pending[*node] == 0)
+            .collect();
+        let mut visited = 0;
+        let mut order = vec![];
+        while let Some(node) = ready.pop() {
+            visited += 1;
+            if matches!(nodes[node].kind, NodeKind::Hidden | NodeKind::Output) {
+                order.push(node);
+            }
+            for dependent in &dependents[node] {
+                pending[*dependent] -= 1;
+                if pending[*dependent] == 0 {
+                    ready.push(*dependent);
+                }
+            }
+        }
+        (visited == nodes.len()).then_some(Network { order, incoming })
+    }
+}
+
+/// A genome after NEAT (NeuroEvolution of Augmenting Topologies) that evolves the shape of its network along with the weights.
+///
+/// It starts with every input connected to every output and grows hidden neurons and connections through mutation.
+/// The network stays feed-forward, mutations never add a connection that closes a cycle.
+#[derive(Clone)]
+pub struct Neat {
+    inputs: usize,
+    outputs: usize,
+    /// Activation of the hidden neurons added by mutation
+    hidden_activation: Activation,
+    /// The inputs, the bias and the outputs, followed by the hidden neurons in the order they appeared
+    nodes: Vec<NodeGene>,
+    connections: Vec<ConnectionGene>,
+    /// The rate the genome was mutated with, see [`Reproduction::child_mutation_rate`]
+    mutation_rate: f64,
+    network: Network,
+}
+
+impl Neat {
+    fn new(
+        inputs: usize,
+        outputs: usize,
+        hidden_activation: Activation,
+        nodes: Vec<NodeGene>,
+        connections: Vec<ConnectionGene>,
+        mutation_rate: f64,
+    ) -> Option<Neat> {
+        let network = Network::compile(&nodes, &connections)?;
+        Some(Neat {
+            inputs,
+            outputs,
+            hidden_activation,
+            nodes,
+            connections,
+            mutation_rate,
+            network,
+        })
+    }
+
+    /// The neurons every genome with these inputs and outputs has, the ids are their indices
+    fn fixed_nodes(inputs: usize, outputs: usize, output_activation: Activation) -> Vec<NodeGene> {
+        let kinds = std::iter::repeat_n(NodeKind::Input, inputs)
+            .chain(std::iter::once(NodeKind::Bias))
+            .chain(std::iter::repeat_n(NodeKind::Output, outputs));
+        kinds
+            .enumerate()
+            .map(|(id, kind)| NodeGene {
+                id: id as u64,
+                kind,
+                activation: match kind {
+                    NodeKind::Output => output_activation,
+                    _ => Activation::None,
+                },
+            })
+            .collect()
+    }
+
+    /// Whether `goal` can be reached from `start` by following the connections, disabled ones included
+    fn reaches(&self, start: u64, goal: u64) -> bool {
+        let mut stack = vec![start];
+        let mut seen = HashSet::new();
+        while let Some(node) = stack.pop() {
+            if node == goal {
+                return true;
+            }
+            if seen.insert(node) {
+                stack.extend(
+                    self.connections
+                        .iter()
+                        .filter(|connection| connection.from == node)
+                        .map(|connection| connection.to),
+                );
+            }
+        }
+        false
+    }
+
+    /// Connects two random neurons that are not connected yet, unless that would close a cycle.
+    fn add_connection(&mut self, rng: &mut StdRng) {
+        let sources: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter(|node| node.kind != NodeKind::Output)
+            .map(|node| node.id)
+            .collect();
+        let targets: Vec<u64> = self
+            .nodes
+            .iter()
+            .filter(|node| matches!(node.kind, NodeKind::Hidden | NodeKind::Output))
+            .map(|node| node.id)
+            .collect();
+
+        for _ in 0..ADD_CONNECTION_ATTEMPTS {
+            let (from, to) = match (sources.choose(rng), targets.choose(rng)) {
+                (Some(from), Some(to)) => (*from, *to),
+                _ => return,
+            };
+            let connected = self
+                .connections
+                .iter()
+                .any(|connection| connection.from == from && connection.to == to);
+            if from == to || connected || self.reaches(to, from) {
+                continue;
+            }
+            self.connections.push(ConnectionGene {
+                from,
+                to,
+                weight: rng.gen_range(-1.0..1.0),
+                enabled: true,
+                step_size: 0.0,
+            });
+            return;
+        }
+    }
+
+    /// Splits a random enabled connection with a new neuron.
+    ///
+    /// The connection into the neuron gets the weight 1 and the one out of it the old weight, so the network barely changes at first.
+    fn add_node(&mut self, rng: &mut StdRng) {
+        let enabled: Vec<usize> = (0..self.connections.len())
+            .filter(|index| self.connections[*index].enabled)
+            .collect();
+        let split = match enabled.choose(rng) {
+            Some(split) => *split,
+            None => return,
+        };
+        let id = split_node(self.connections[split].innovation());
+        // The connection was split before and enabled again by a crossover
+        if self.nodes.iter().any(|node| node.id == id) {
+            return;
+        }
+
+        let connection = &mut self.connections[split];
+        connection.enabled = false;
+        let (from, to, weight) = (connection.from, connection.to, connection.weight);
+        self.nodes.push(NodeGene {
+            id,
+            kind: NodeKind::Hidden,
+            activation: self.hidden_activation,
+        });
+        self.connections.extend([
+            ConnectionGene {
+                from,
+                to: id,
+                weight: 1.0,
+                enabled: true,
+                step_size: 0.0,
+            },
+            ConnectionGene {
+                from: id,
+                to,
+                weight,
+                enabled: true,
+                step_size: 0.0,
+            },
+        ]);
+    }
+}
+
+impl Brain for Neat {
+    const MAGIC: &'static [u8; 4] = b"SNKN";
+    /// Version 2 added the mutation rate
+    const VERSION: u32 = 2;
+
+    /// Connects every input and the bias to every output, there are no hidden neurons yet.
+    ///
+    /// Only the activations of the topology are used: the first hidden one for the neurons added later and the output one.
+    fn random(inputs: usize, outputs: usize, topology: &Topology, rng: &mut StdRng) -> Self {
+        let hidden_activation = topology
+            .hidden
+            .first()
+            .map_or(Activation::ReLU, |(_, activation)| *activation);
+        let nodes = Neat::fixed_nodes(inputs, outputs, topology.output_activation);
+        let bound = 1.0 / ((inputs + 1) as f32).sqrt();
+        let connections = (inputs + 1..=inputs + outputs)
+            .flat_map(|to| (0..=inputs).map(move |from| (from as u64, to as u64)))
+            .map(|(from, to)| ConnectionGene {
+                from,
+                to,
+                weight: rng.gen_range(-bound..bound),
+                enabled: true,
+                step_size: 0.0,
+            })
+            .collect();
+        Neat::new(inputs, outputs, hidden_activation, nodes, connections, 0.0)
+            .expect("Connections from the inputs to the outputs cannot form a cycle")
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let mut values = vec![0.0; self.nodes.len()];
+        values[..self.inputs]
+            .iter_mut()
+            .zip(input)
+            .for_each(|(value, input)| *value = *input);
+        values[self.inputs] = 1.0;
+        for node in &self.network.order {
+            let sum: f32 = self.network.incoming[*node]
+                .iter()
+                .map(|(source, weight)| values[*source] * weight)
+                .sum();
+            values[*node] = self.nodes[*node].activation.apply(sum);
+        }
+        values[self.inputs + 1..=self.inputs + self.outputs].to_vec()
+    }
+
+    /**
+     * Connections are aligned by their innovation numbers and the weights of the matching ones are combined with the
+     * crossover operator. A connection only one parent has is inherited with a probability of one half, which is what NEAT
+     * does for parents of equal fitness, since an organism does not know which of its parents was fitter.
+     * Connections of `b` that would close a cycle are left out.
+     *
+     * The weights are mutated and guarded like the ones of a layered genome, then the structure may grow.
+     */
+    fn crossover(&self, b: &Neat, reproduction: &Reproduction, rng: &mut StdRng) -> Neat {
+        let b_connections: HashMap<u64, &ConnectionGene> = b
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation(), connection))
+            .collect();
+        let matching: Vec<(&ConnectionGene, &ConnectionGene)> = self
+            .connections
+            .iter()
+            .filter_map(|a| b_connections.get(&a.innovation()).map(|b| (a, *b)))
+            .collect();
+        let a_weights: Vec<f32> = matching.iter().map(|(a, _)| a.weight).collect();
+        let b_weights: Vec<f32> = matching.iter().map(|(_, b)| b.weight).collect();
+        let mut weights = if matching.is_empty() {
+            vec![]
+        } else {
+            reproduction
+                .crossover
+                .crossover(&a_weights, &b_weights, rng)
+        }
+        .into_iter();
+
+        // Every connection of the child next to the weight of the parent it came from, the gene guard falls back to it
+        let mut child = self.clone();
+        child.mutation_rate =
+            reproduction.child_mutation_rate(self.mutation_rate, b.mutation_rate, rng);
+        let mut parent_weights = vec![];
+        child.connections.clear();
+        for a in &self.connections {
+            match b_connections.get(&a.innovation()) {
+                Some(b) => {
+                    let mut connection = a.clone();
+                    connection.weight = weights.next().expect("One weight per matching connection");
+                    connection.step_size = inherit_step_size(a.step_size, b.step_size);
+                    if !a.enabled || !b.enabled {
+                        connection.enabled = !rng.gen_bool(KEEP_DISABLED_PROBABILITY);
+                    }
+                    child.connections.push(connection);
+                    parent_weights.push(a.weight);
+                }
+                None if rng.gen_bool(0.5) => {
+                    child.connections.push(a.clone());
+                    parent_weights.push(a.weight);
+                }
+                None => {}
+            }
+        }
+        let own: HashSet<u64> = self
+            .connections
+            .iter()
+            .map(ConnectionGene::innovation)
+            .collect();
+        for b in b
+            .connections
+            .iter()
+            .filter(|b| !own.contains(&b.innovation()))
+        {
+            if rng.gen_bool(0.5) && !child.reaches(b.to, b.from) {
+                child.connections.push(b.clone());
+                parent_weights.push(b.weight);
+            }
+        }
+
+        for (connection, parent_weight) in child.connections.iter_mut().zip(parent_weights) {
+            if rng.gen_bool(child.mutation_rate) {
+                connection.weight =
+                    reproduction
+                        .mutation
+                        .mutate(connection.weight, &mut connection.step_size, rng);
+            }
+            connection.weight = reproduction.guard.apply(connection.weight, parent_weight);
+        }
+
+        // Keep the fixed neurons and the hidden ones of both parents that are still connected
+        let connected: HashSet<u64> = child
+            .connections
+            .iter()
+            .flat_map(|connection| [connection.from, connection.to])
+            .collect();
+        let fixed = self.inputs + 1 + self.outputs;
+        let mut seen = HashSet::new();
+        child.nodes = self.nodes[..fixed]
+            .iter()
+            .cloned()
+            .chain(
+                self.nodes[fixed..]
+                    .iter()
+                    .chain(&b.nodes[fixed..])
+                    .filter(|node| connected.contains(&node.id) && seen.insert(node.id))
+                    .cloned(),
+            )
+            .collect();
+
+        if rng.gen_bool(ADD_CONNECTION_PROBABILITY) {
+            child.add_connection(rng);
+        }
+        if rng.gen_bool(ADD_NODE_PROBABILITY) {
+            child.add_node(rng);
+        }
+        child.network = Network::compile(&child.nodes, &child.connections)
+            .expect("Crossover and mutations never close a cycle");
+        child
+    }
+
+    /// The compatibility distance of NEAT: the share of connections only one genome has plus the mean weight difference of the others.
+    ///
+    /// Innovation numbers derived from the neurons have no order, so disjoint and excess connections are not told apart.
+    fn distance(&self, other: &Neat) -> f32 {
+        let other_weights: HashMap<u64, f32> = other
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation(), connection.weight))
+            .collect();
+        let (matching, weight_difference) = self
+            .connections
+            .iter()
+            .filter_map(|connection| {
+                other_weights
+                    .get(&connection.innovation())
+                    .map(|weight| (connection.weight as f64 - *weight as f64).abs())
+            })
+            .fold((0, 0.0), |(count, sum), difference| {
+                (count + 1, sum + difference)
+            });
+        let disjoint = self.connections.len() + other.connections.len() - 2 * matching;
+        let genes = self.connections.len().max(other.connections.len()).max(1);
+        let distance = DISJOINT_COEFFICIENT * disjoint as f64 / genes as f64
+            + WEIGHT_COEFFICIENT * weight_difference / matching.max(1) as f64;
+        distance as f32
+    }
+
+    fn mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    /// The hidden neurons grow on their own, so only the inputs and outputs have to match.
+    fn check_shape(
+        &self,
+        inputs: usize,
+        outputs: usize,
+        _topology: &Topology,
+    ) -> Result<(), String> {
+        if (self.inputs, self.outputs) != (inputs, outputs) {
+            return Err(format!(
+                "The saved network has {} inputs and {} outputs, but the chosen sensors need {} and {}",
+                self.inputs, self.outputs, inputs, outputs
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Persist for NodeKind {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let code: u8 = match self {
+            NodeKind::Input => 0,
+            NodeKind::Bias => 1,
+            NodeKind::Hidden => 2,
+            NodeKind::Output => 3,
+        };
+        code.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(NodeKind::Input),
+            1 => Ok(NodeKind::Bias),
+            2 => Ok(NodeKind::Hidden),
+            3 => Ok(NodeKind::Output),
+            code => Err(invalid_data(format!("Unknown node kind {}", code))),
+        }
+    }
+}
+
+impl Persist for NodeGene {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.id.write_to(writer)?;
+        self.kind.write_to(writer)?;
+        self.activation.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(NodeGene {
+            id: u64::read_from(reader)?,
+            kind: NodeKind::read_from(reader)?,
+            activation: Activation::read_from(reader)?,
+        })
+    }
+}
+
+/// The innovation number is not stored, it follows from the neurons.
+impl Persist for ConnectionGene {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.from.write_to(writer)?;
+        self.to.write_to(writer)?;
+        self.weight.write_to(writer)?;
+        (self.enabled as u8).write_to(writer)?;
+        self.step_size.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(ConnectionGene {
+            from: u64::read_from(reader)?,
+            to: u64::read_from(reader)?,
+            weight: f32::read_from(reader)?,
+            enabled: u8::read_from(reader)? != 0,
+            step_size: f32::read_from(reader)?,
+        })
+    }
+}
+
+/// A NEAT genome is stored as its number of inputs and outputs, the activation of new neurons, its neurons, its connections and its mutation rate.
+impl Persist for Neat {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        (self.inputs as u32).write_to(writer)?;
+        (self.outputs as u32).write_to(writer)?;
+        self.hidden_activation.write_to(writer)?;
+        self.nodes.write_to(writer)?;
+        self.connections.write_to(writer)?;
+        self.mutation_rate.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let inputs = u32::read_from(reader)? as usize;
+        let outputs = u32::read_from(reader)? as usize;
+        let hidden_activation = Activation::read_from(reader)?;
+        let nodes: Vec<NodeGene> = Vec::read_from(reader)?;
+        let connections: Vec<ConnectionGene> = Vec::read_from(reader)?;
+        let mutation_rate = f64::read_from(reader)?;
+
+        let fixed = inputs + 1 + outputs;
+        let fixed_match = nodes.len() >= fixed
+            && nodes[..fixed]
+                .iter()
+                .zip(Neat::fixed_nodes(inputs, outputs, Activation::None))
+                .all(|(node, expected)| node.id == expected.id && node.kind == expected.kind);
+        let hidden_match = nodes[fixed.min(nodes.len())..]
+            .iter()
+            .all(|node| node.kind == NodeKind::Hidden && node.id & HIDDEN_NODE != 0);
+        if !fixed_match || !hidden_match {
+            return Err(invalid_data(
+                "The neurons of the genome do not match its inputs and outputs",
+            ));
+        }
+        let kinds: HashMap<u64, NodeKind> = nodes.iter().map(|node| (node.id, node.kind)).collect();
+        let mut pairs = HashSet::new();
+        let valid_connections = connections.iter().all(|connection| {
+            pairs.insert((connection.from, connection.to))
+                && kinds
+                    .get(&connection.from)
+                    .is_some_and(|kind| *kind != NodeKind::Output)
+                && kinds
+                    .get(&connection.to)
+                    .is_some_and(|kind| matches!(kind, NodeKind::Hidden | NodeKind::Output))
+        });
+        if kinds.len() != nodes.len() || !valid_connections {
+            return Err(invalid_data("The connections of the genome are invalid"));
+        }
+        if !(0.0..=1.0).contains(&mutation_rate) {
+            return Err(invalid_data(format!(
+                "The mutation rate {} of the genome does not lie between 0 and 1",
+                mutation_rate
+            )));
+        }
+        Neat::new(
+            inputs,
+            outputs,
+            hidden_activation,
+            nodes,
+            connections,
+            mutation_rate,
+        )
+        .ok_or_else(|| invalid_data("The connections of the genome form a cycle"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn grown_genome_survives_a_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let topology = Topology {
+            hidden: vec![(0, Activation::Tanh)],
+            output_activation: Activation::Sigmoid,
+        };
+        let mut neat = Neat::random(6, 4, &topology, &mut rng);
+        for _ in 0..5 {
+            neat.add_node(&mut rng);
+            neat.add_connection(&mut rng);
+        }
+        let neat = Neat::new(
+            6,
+            4,
+            neat.hidden_activation,
+            neat.nodes,
+            neat.connections,
+            0.02,
+        )
+        .unwrap();
+        assert!(neat.nodes.len() > 6 + 1 + 4);
+
+        let mut bytes = vec![];
+        neat.write_to(&mut bytes).unwrap();
+        let loaded = Neat::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(loaded.nodes.len(), neat.nodes.len());
+        assert_eq!(loaded.connections.len(), neat.connections.len());
+        assert_eq!(loaded.mutation_rate, neat.mutation_rate);
+        let input = [0.5, -1.0, 2.0, 0.0, 3.0, -0.25];
+        assert_eq!(loaded.forward(&input), neat.forward(&input));
+    }
+
+    #[test]
+    fn connection_into_an_input_is_rejected() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let topology = Topology {
+            hidden: vec![],
+            output_activation: Activation::Sigmoid,
+        };
+        let mut neat = Neat::random(2, 1, &topology, &mut rng);
+        neat.connections[0].to = 0;
+
+        let mut bytes = vec![];
+        neat.write_to(&mut bytes).unwrap();
+        assert!(Neat::read_from(&mut bytes.as_slice()).is_err());
+    }
+}
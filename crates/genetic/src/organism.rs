@@ -0,0 +1,84 @@
+use rand::rngs::StdRng;
+
+use crate::crossover::{CrossoverOperator, GeneGuard};
+use crate::mutation::MutationOperator;
+use crate::mutation_rate::MutationRateController;
+use crate::persistence::Persist;
+
+/// How the population wants its children to be made, handed to [`Organism::crossover`].
+pub struct Reproduction<'a> {
+    pub crossover: &'a dyn CrossoverOperator,
+    pub guard: GeneGuard,
+    /// Probability of every single gene to mutate, unless the parents carry their own, see [`Reproduction::child_mutation_rate`]
+    pub mutation_rate: f64,
+    /// Lower and upper bound of the mutation rate
+    pub mutation_bounds: (f64, f64),
+    pub mutation_control: &'a dyn MutationRateController,
+    pub mutation: &'a dyn MutationOperator,
+}
+
+impl Reproduction<'_> {
+    /// The mutation rate of a child whose parents carry the rates `a` and `b`, the child carries it on.
+    pub fn child_mutation_rate(&self, a: f64, b: f64, rng: &mut StdRng) -> f64 {
+        let (min, max) = self.mutation_bounds;
+        self.mutation_control
+            .inherit(self.mutation_rate, a, b, rng)
+            .clamp(min, max)
+    }
+}
+
+pub trait Organism: Clone + Send {
+    /// Settings shared by all organisms of a population, e.g. the rules of the game they play
+    type Config: Clone + Send + Sync;
+
+    /// The inherited part of an organism, this is what checkpoints store
+    type Genotype: Persist;
+
+    /// Tells the kinds of genotypes apart in checkpoints
+    const GENOTYPE: &'static [u8; 4];
+
+    /// Create a new random organism
+    ///
+    /// The organism should derive its own random number stream from `rng`,
+    /// so that ticking organisms in parallel stays deterministic.
+    fn random(config: &Self::Config, rng: &mut StdRng) -> Self;
+
+    /// Create a new organism with the given genotype, `seed` starts its random number stream
+    fn from_genotype(genotype: Self::Genotype, config: &Self::Config, seed: u64) -> Self;
+
+    /// Get the genotype of this organism
+    fn get_genotype(&self) -> &Self::Genotype;
+
+    /// Get the current fitness of an organism
+    fn get_fitness(&self) -> f32;
+
+    /// The ways an organism can die, the statistics count the deaths per cause
+    const DEATH_CAUSES: &'static [&'static str];
+
+    /// Why the organism died, one of [`Organism::DEATH_CAUSES`], `None` while it is alive or if it did not die of a cause
+    fn death_cause(&self) -> Option<&'static str>;
+
+    /// How different the genotypes of two organisms are, 0 if they are identical
+    fn distance(&self, other: &Self) -> f32;
+
+    /// The mutation rate the genotype carries, 0 if it never carried one
+    fn mutation_rate(&self) -> f64;
+
+    /// Create a new organism from two parents
+    fn crossover(&self, b: &Self, reproduction: &Reproduction, rng: &mut StdRng) -> Self;
+
+    /// Check if this organism is alive
+    fn is_alive(&self) -> bool;
+
+    /// Reset this organism to its initial state
+    fn reborn(&mut self);
+
+    /// Restart the random number stream of this organism, the next [`Organism::reborn`] draws from it
+    fn reseed(&mut self, seed: u64);
+
+    /// Kill the organism on the next tick
+    fn kill(&mut self);
+
+    /// Process the next tick
+    fn tick(&mut self);
+}
@@ -0,0 +1,94 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Binary (little endian) serialization used for checkpoints.
+///
+/// I did not want to pull in serde just for a handful of numbers.
+pub trait Persist: Sized {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()>;
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self>;
+}
+
+pub fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Writes a file starting with `magic` and `version`, followed by whatever `write` produces.
+///
+/// The file is written next to the target first and renamed afterwards,
+/// so an interrupted save never destroys the previous checkpoint.
+pub fn save_file(
+    path: &Path,
+    magic: &[u8; 4],
+    version: u32,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&temporary)?);
+    writer.write_all(magic)?;
+    version.write_to(&mut writer)?;
+    write(&mut writer)?;
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error())?
+        .sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+/// Opens a file written by [`save_file`] and checks its header.
+pub fn open_file(path: &Path, magic: &[u8; 4], version: u32) -> io::Result<BufReader<File>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut found_magic = [0u8; 4];
+    reader.read_exact(&mut found_magic)?;
+    if &found_magic != magic {
+        return Err(invalid_data(format!(
+            "{} is not a {} file",
+            path.display(),
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    let found_version = u32::read_from(&mut reader)?;
+    if found_version != version {
+        return Err(invalid_data(format!(
+            "{} has version {}, expected {}",
+            path.display(),
+            found_version,
+            version
+        )));
+    }
+    Ok(reader)
+}
+
+macro_rules! persist_number {
+    ($($number:ty),*) => {
+        $(
+            impl Persist for $number {
+                fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
+                fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+                    let mut bytes = [0u8; std::mem::size_of::<$number>()];
+                    reader.read_exact(&mut bytes)?;
+                    Ok(<$number>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+persist_number!(u8, u32, u64, i32, f32, f64);
+
+impl<T: Persist> Persist for Vec<T> {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        (self.len() as u64).write_to(writer)?;
+        self.iter().try_for_each(|item| item.write_to(writer))
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let len = u64::read_from(reader)?;
+        (0..len).map(|_| T::read_from(reader)).collect()
+    }
+}
@@ -0,0 +1,663 @@
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::crossover::{BitSplice, CrossoverOperator, GeneGuard};
+use crate::evaluation::Evaluation;
+use crate::mutation::{MutationOperator, UniformReset};
+use crate::mutation_rate::{MutationRateController, Outcome, ProgressStep};
+use crate::organism::{Organism, Reproduction};
+use crate::persistence::{self, invalid_data, Persist};
+use crate::selection::{SelectionStrategy, Truncation};
+use crate::speciation::{self, Speciation, Species};
+use crate::stats::GenerationStats;
+
+use rayon::prelude::*;
+
+/// Run parameters of a population.
+#[derive(Clone, Debug)]
+pub struct PopulationConfig {
+    /// Number of organisms in the population
+    pub capacity: usize,
+    /// Mutation rate of the first generation
+    pub mutation_rate: f64,
+    /// Lower bound for the adaptive mutation rate
+    pub min_mutation_rate: f64,
+    /// Upper bound for the adaptive mutation rate
+    pub max_mutation_rate: f64,
+    /// How the mutation rate adapts from one generation to the next
+    pub mutation_control: Arc<dyn MutationRateController>,
+    /// How many episodes an organism plays and how they add up to its fitness
+    pub evaluation: Evaluation,
+    /// How many of the best organisms move on to the next generation unchanged
+    pub elitism: Elitism,
+    /// Which organisms make room for the children
+    pub replacement: Replacement,
+    /// How the parents are picked
+    pub selection: Arc<dyn SelectionStrategy>,
+    /// How the genes of two parents are combined
+    pub crossover: Arc<dyn CrossoverOperator>,
+    /// What happens to genes that end up infinite or NaN
+    pub gene_guard: GeneGuard,
+    /// How the genes picked by the mutation rate are changed
+    pub mutation: Arc<dyn MutationOperator>,
+    /// Breed within species of similar organisms instead of the whole population
+    pub speciation: Option<Speciation>,
+    /// Master seed of the run, a random one is used if none is given
+    ///
+    /// The same seed and config always produce the same generations.
+    pub seed: Option<u64>,
+}
+
+impl Default for PopulationConfig {
+    /// The algorithm this project started with, before its parts became selectable:
+    /// truncation to the best tenth, bit splicing, uniform resets and the mutation rate nudged by 10% per generation.
+    fn default() -> Self {
+        PopulationConfig {
+            capacity: 2000,
+            mutation_rate: 0.01,
+            min_mutation_rate: 0.00005,
+            max_mutation_rate: 0.05,
+            mutation_control: Arc::new(ProgressStep { factor: 0.1 }),
+            evaluation: Evaluation::default(),
+            elitism: Elitism::Count(0),
+            replacement: Replacement::Generational,
+            selection: Arc::new(Truncation { fraction: 0.1 }),
+            crossover: Arc::new(BitSplice),
+            gene_guard: GeneGuard::default(),
+            mutation: Arc::new(UniformReset { range: 3.0 }),
+            speciation: None,
+            seed: None,
+        }
+    }
+}
+
+/// The number of elites, either absolute or as a fraction of the capacity.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Elitism {
+    Count(usize),
+    Fraction(f64),
+}
+
+impl Elitism {
+    /// The number of elites in a population of `capacity` organisms
+    pub fn count(self, capacity: usize) -> usize {
+        match self {
+            Elitism::Count(count) => count.min(capacity),
+            Elitism::Fraction(fraction) => {
+                ((capacity as f64 * fraction).round() as usize).min(capacity)
+            }
+        }
+    }
+}
+
+/// How the next generation is put together. The population always keeps its capacity.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Replacement {
+    /// Children replace everyone but the elites
+    Generational,
+    /// Children replace the `replacements` worst organisms, everyone else moves on
+    SteadyState { replacements: usize },
+    /// The best `mu` organisms breed and move on together with their children
+    MuPlusLambda { mu: usize },
+    /// The best `mu` organisms breed, but only their children (and the elites) move on
+    MuCommaLambda { mu: usize },
+}
+
+impl Replacement {
+    /// The number of best organisms that move on and the number of best organisms that may breed
+    fn survivors_and_breeders(self, capacity: usize) -> (usize, usize) {
+        match self {
+            Replacement::Generational => (0, capacity),
+            Replacement::SteadyState { replacements } => {
+                (capacity.saturating_sub(replacements), capacity)
+            }
+            Replacement::MuPlusLambda { mu } => (mu, mu),
+            Replacement::MuCommaLambda { mu } => (0, mu),
+        }
+    }
+}
+
+const POPULATION_MAGIC: &[u8; 4] = b"SNKP";
+/// Version 2 added the mutation step sizes to the layers, version 3 the kind of genotype, version 4 the mutation rate of every genotype
+const POPULATION_VERSION: u32 = 4;
+
+pub struct Population<T> {
+    config: PopulationConfig,
+    genomes: Vec<T>,
+    rng: StdRng,
+    pub alive_genomes_count: usize,
+    /// The best fitness of the current generation, negative infinity while there is none
+    pub max_fitness_current: f32,
+
+    pub generation: usize,
+    /// The best fitness of the previous generation, negative infinity before the first one
+    pub max_fitness_prev: f32,
+    pub mutation_rate: f64,
+    /// Whether an elite was the best organism of the previous generation, see [`Population::elite_won`]
+    pub elite_won_prev: Option<bool>,
+    /// The elites of the current generation are the first organisms of `genomes`
+    elites: usize,
+    /// The episode the organisms currently play, counted from 0 in every generation
+    pub episode: usize,
+    /// The scores of the finished episodes of every organism
+    scores: Vec<Vec<f32>>,
+    /// The deaths per cause of the finished episodes, in the order of [`Organism::DEATH_CAUSES`]
+    deaths: Vec<usize>,
+    generation_started: Instant,
+    /// The species of the current generation, empty without speciation
+    species: Vec<Species<T>>,
+    /// The mean fitness of the parents of every organism, `None` for the ones that were not born in the last evolution
+    parent_fitnesses: Vec<Option<f32>>,
+}
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+impl<T: Organism> Population<T> {
+    pub fn new(config: PopulationConfig, organism_config: &T::Config) -> Self {
+        let mut rng = Self::master_rng(&config);
+        let genomes: Vec<T> = (0..config.capacity)
+            .map(|_| T::random(organism_config, &mut rng))
+            .collect();
+        let mut population = Population {
+            genomes,
+            rng,
+            alive_genomes_count: 0,
+            max_fitness_current: f32::NEG_INFINITY,
+            mutation_rate: config.mutation_rate,
+            generation: 0,
+            max_fitness_prev: f32::NEG_INFINITY,
+            elite_won_prev: None,
+            elites: 0,
+            episode: 0,
+            scores: vec![],
+            deaths: vec![],
+            generation_started: Instant::now(),
+            species: vec![],
+            parent_fitnesses: vec![None; config.capacity],
+            config,
+        };
+        population.start_episode(0);
+        population
+    }
+
+    fn master_rng(config: &PopulationConfig) -> StdRng {
+        match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Restarts every organism for the given episode, a new generation starts with episode 0.
+    fn start_episode(&mut self, episode: usize) {
+        if episode == 0 {
+            self.scores = vec![Vec::new(); self.genomes.len()];
+            self.deaths = vec![0; T::DEATH_CAUSES.len()];
+            self.generation_started = Instant::now();
+            if let Some(speciation) = self.config.speciation {
+                speciation::speciate(&mut self.species, &self.genomes, speciation.threshold);
+            }
+        }
+        self.episode = episode;
+        if let Some(seed) = self.config.evaluation.episode_seed(episode) {
+            self.genomes
+                .iter_mut()
+                .for_each(|organism| organism.reseed(seed));
+        }
+        self.reborn();
+    }
+
+    pub fn reborn(&mut self) {
+        self.genomes.iter_mut().for_each(|genome| {
+            genome.reborn();
+        });
+    }
+
+    pub fn kill(&mut self) {
+        self.genomes.iter_mut().for_each(|genome| {
+            genome.kill();
+        });
+    }
+
+    pub fn tick(&mut self) {
+        struct TickResult {
+            best_fitness: f32,
+            survivors: usize,
+        }
+
+        // Rayon parallel iter => nice
+        //
+        // I refactored it to use map so we dont need to use a mutex
+        //
+        // Removed batches as rayon handles it anyways. I think that sped up the process a bit, but did no real benchmarking
+        //
+        // Even without batching rayon creates only 20 threads on my computer, so I think we are fine
+        let tick_result = self
+            .genomes
+            .par_iter_mut()
+            .map(|organism| {
+                if !organism.is_alive() {
+                    return TickResult {
+                        best_fitness: f32::NEG_INFINITY,
+                        survivors: 0,
+                    };
+                }
+                organism.tick();
+                let organism_fitness = organism.get_fitness();
+                TickResult {
+                    best_fitness: organism_fitness,
+                    survivors: 1,
+                }
+            })
+            .reduce(
+                // Fitness can be negative, so nobody alive means no best fitness rather than 0
+                || TickResult {
+                    best_fitness: f32::NEG_INFINITY,
+                    survivors: 0,
+                },
+                |result, batch_result| TickResult {
+                    best_fitness: result.best_fitness.max(batch_result.best_fitness),
+                    survivors: result.survivors + batch_result.survivors,
+                },
+            );
+
+        self.max_fitness_current = tick_result.best_fitness;
+        self.alive_genomes_count = tick_result.survivors;
+
+        if self.is_dead() && self.episode + 1 < self.config.evaluation.episodes {
+            self.scores
+                .iter_mut()
+                .zip(&self.genomes)
+                .for_each(|(scores, organism)| scores.push(organism.get_fitness()));
+            self.deaths = self.deaths_with_running_episode();
+            self.start_episode(self.episode + 1);
+            self.alive_genomes_count = self.genomes.len();
+        }
+    }
+
+    /// The fitness of every organism over all episodes of this generation, including the running one.
+    pub fn fitnesses(&self) -> Vec<f32> {
+        self.genomes
+            .iter()
+            .zip(&self.scores)
+            .map(|(organism, scores)| {
+                let mut scores = scores.clone();
+                scores.push(organism.get_fitness());
+                self.config.evaluation.aggregation.aggregate(scores)
+            })
+            .collect()
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.alive_genomes_count == 0
+    }
+
+    /// The deaths per cause of the whole generation so far
+    fn deaths_with_running_episode(&self) -> Vec<usize> {
+        let mut deaths = self.deaths.clone();
+        self.genomes
+            .iter()
+            .filter_map(|organism| organism.death_cause())
+            .filter_map(|cause| T::DEATH_CAUSES.iter().position(|known| *known == cause))
+            .for_each(|index| deaths[index] += 1);
+        deaths
+    }
+
+    /// The mean distance of every organism to the one half a population away.
+    ///
+    /// Comparing all pairs is quadratic, this samples one partner per organism instead.
+    pub fn diversity(&self) -> f32 {
+        let count = self.genomes.len();
+        if count < 2 {
+            return 0.0;
+        }
+        let sum: f32 = (0..count)
+            .map(|i| self.genomes[i].distance(&self.genomes[(i + count / 2) % count]))
+            .sum();
+        sum / count as f32
+    }
+
+    /// The number of species of the current generation, `None` without speciation
+    pub fn species_count(&self) -> Option<usize> {
+        self.config.speciation.map(|_| self.species.len())
+    }
+
+    /// Summarises the current generation, call it before [`Population::evolution`].
+    pub fn stats(&self) -> GenerationStats {
+        GenerationStats {
+            generation: self.generation,
+            species: self.species_count(),
+            mutation_rate: self.mutation_rate,
+            diversity: self.diversity(),
+            duration: self.generation_started.elapsed(),
+            deaths: T::DEATH_CAUSES
+                .iter()
+                .copied()
+                .zip(self.deaths_with_running_episode())
+                .collect(),
+            ..GenerationStats::from_fitnesses(self.fitnesses())
+        }
+    }
+
+    /// Whether one of the elites carried over from the previous generation is (one of) the best of the current one.
+    ///
+    /// `None` if the generation has no elites.
+    pub fn elite_won(&self) -> Option<bool> {
+        let fitnesses = self.fitnesses();
+        let best = |fitnesses: &[f32]| fitnesses.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        (self.elites > 0).then(|| best(&fitnesses[..self.elites]) >= best(&fitnesses))
+    }
+
+    /// The share of the organisms born in the last evolution that beat the mean fitness of their parents
+    fn success_rate(&self, fitnesses: &[f32]) -> Option<f64> {
+        let (successes, children) = fitnesses
+            .iter()
+            .zip(&self.parent_fitnesses)
+            .filter_map(|(fitness, parents)| parents.map(|parents| fitness > &parents))
+            .fold((0, 0), |(successes, children), success| {
+                (successes + success as usize, children + 1)
+            });
+        (children > 0).then(|| successes as f64 / children as f64)
+    }
+
+    pub fn evolution(&mut self) {
+        self.elite_won_prev = self.elite_won();
+        let fitnesses = self.fitnesses();
+        let success_rate = self.success_rate(&fitnesses);
+        let diversity = self.diversity();
+        let carried_rates: Vec<f64> = self
+            .genomes
+            .iter()
+            .map(|organism| organism.mutation_rate())
+            .collect();
+        let mut ranked: Vec<(f32, usize, T)> = fitnesses
+            .into_iter()
+            .zip(self.genomes.drain(..))
+            .enumerate()
+            .map(|(index, (fitness, organism))| (fitness, index, organism))
+            .collect();
+        ranked.sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+        let mut rank = vec![0; ranked.len()];
+        let mut fitnesses = Vec::with_capacity(ranked.len());
+        for (position, (fitness, index, organism)) in ranked.into_iter().enumerate() {
+            rank[index] = position;
+            fitnesses.push(fitness);
+            self.genomes.push(organism);
+        }
+        speciation::rank(&mut self.species, &self.genomes, &fitnesses, &rank);
+        // The best of the generation over all episodes, not just the last survivor
+        self.max_fitness_current = fitnesses[0];
+
+        let outcome = Outcome {
+            generation: self.generation,
+            initial_rate: self.config.mutation_rate,
+            best_fitness: self.max_fitness_current,
+            previous_best_fitness: self.max_fitness_prev,
+            success_rate,
+            diversity,
+            carried_rates: &carried_rates,
+        };
+        self.max_fitness_prev = self.max_fitness_current;
+
+        self.mutation_rate = f64::clamp(
+            self.config
+                .mutation_control
+                .adapt(self.mutation_rate, &outcome),
+            self.config.min_mutation_rate,
+            self.config.max_mutation_rate,
+        );
+
+        let reproduction = Reproduction {
+            crossover: self.config.crossover.as_ref(),
+            guard: self.config.gene_guard,
+            mutation_rate: self.mutation_rate,
+            mutation_bounds: self.mutation_bounds(),
+            mutation_control: self.config.mutation_control.as_ref(),
+            mutation: self.config.mutation.as_ref(),
+        };
+        let capacity = self.config.capacity;
+        self.elites = self.config.elitism.count(capacity);
+        let (survivors, breeders) = self.config.replacement.survivors_and_breeders(capacity);
+        let survivors = survivors.max(self.elites).min(capacity);
+        let breeders = breeders.clamp(1, fitnesses.len());
+        let parents = match &self.config.speciation {
+            Some(speciation) => speciation::select_parents(
+                &self.species,
+                speciation,
+                &fitnesses,
+                breeders,
+                capacity - survivors,
+                self.config.selection.as_ref(),
+                &mut self.rng,
+            ),
+            None => self.config.selection.select(
+                &fitnesses[..breeders],
+                2 * (capacity - survivors),
+                &mut self.rng,
+            ),
+        };
+        // The genomes are sorted, so the elites and other survivors come first. They are reborn with everyone else below.
+        let new_population: Vec<T> = self.genomes[..survivors]
+            .iter()
+            .cloned()
+            .chain(parents.chunks_exact(2).map(|pair| {
+                self.genomes[pair[0]].crossover(
+                    &self.genomes[pair[1]],
+                    &reproduction,
+                    &mut self.rng,
+                )
+            }))
+            .collect();
+        self.genomes = new_population;
+        self.parent_fitnesses = std::iter::repeat_n(None, survivors)
+            .chain(
+                parents
+                    .chunks_exact(2)
+                    .map(|pair| Some((fitnesses[pair[0]] + fitnesses[pair[1]]) / 2.0)),
+            )
+            .collect();
+
+        self.start_episode(0);
+        self.generation += 1;
+    }
+
+    /// Stores the current generation and the state of the evolution, so it can be resumed with [`Population::load`].
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        persistence::save_file(path, POPULATION_MAGIC, POPULATION_VERSION, |writer| {
+            (self.generation as u64).write_to(writer)?;
+            self.mutation_rate.write_to(writer)?;
+            self.max_fitness_prev.write_to(writer)?;
+            writer.write_all(T::GENOTYPE)?;
+            (self.genomes.len() as u64).write_to(writer)?;
+            self.genomes
+                .iter()
+                .try_for_each(|organism| organism.get_genotype().write_to(writer))
+        })
+    }
+
+    /// Resumes a population saved with [`Population::save`].
+    ///
+    /// If `config` asks for a different capacity than the saved one, the population is truncated or filled up with new organisms.
+    pub fn load(
+        path: &Path,
+        config: PopulationConfig,
+        organism_config: &T::Config,
+    ) -> io::Result<Self> {
+        let mut reader = persistence::open_file(path, POPULATION_MAGIC, POPULATION_VERSION)?;
+        let generation = u64::read_from(&mut reader)? as usize;
+        let mutation_rate = f64::read_from(&mut reader)?;
+        let max_fitness_prev = f32::read_from(&mut reader)?;
+        let mut genotype = [0u8; 4];
+        reader.read_exact(&mut genotype)?;
+        if &genotype != T::GENOTYPE {
+            return Err(invalid_data(format!(
+                "The population has {} genotypes, expected {}",
+                String::from_utf8_lossy(&genotype),
+                String::from_utf8_lossy(T::GENOTYPE)
+            )));
+        }
+        let count = u64::read_from(&mut reader)?;
+        let genotypes = (0..count)
+            .map(|_| T::Genotype::read_from(&mut reader))
+            .collect::<io::Result<Vec<T::Genotype>>>()?;
+
+        let mut rng = Self::master_rng(&config);
+        let capacity = config.capacity;
+        let mut genomes: Vec<T> = genotypes
+            .into_iter()
+            .take(config.capacity)
+            .map(|genotype| T::from_genotype(genotype, organism_config, rng.gen()))
+            .collect();
+        genomes.resize_with(config.capacity, || T::random(organism_config, &mut rng));
+
+        let mut population = Population {
+            config,
+            genomes,
+            rng,
+            alive_genomes_count: 0,
+            max_fitness_current: f32::NEG_INFINITY,
+            generation,
+            max_fitness_prev,
+            mutation_rate,
+            elite_won_prev: None,
+            elites: 0,
+            episode: 0,
+            scores: vec![],
+            deaths: vec![],
+            generation_started: Instant::now(),
+            species: vec![],
+            parent_fitnesses: vec![None; capacity],
+        };
+        population.start_episode(0);
+        Ok(population)
+    }
+
+    /// The lower and upper bound of the adaptive mutation rate
+    pub fn mutation_bounds(&self) -> (f64, f64) {
+        (self.config.min_mutation_rate, self.config.max_mutation_rate)
+    }
+
+    /// Changes the bounds of the adaptive mutation rate while the evolution runs, the current rate is clamped into them.
+    pub fn set_mutation_bounds(&mut self, min: f64, max: f64) {
+        self.config.min_mutation_rate = min;
+        self.config.max_mutation_rate = max;
+        self.mutation_rate = self.mutation_rate.clamp(min, max);
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.config.capacity
+    }
+
+    pub fn get_genomes(&mut self) -> &mut Vec<T> {
+        &mut self.genomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An organism whose single gene is its fitness, it dies on its first tick
+    #[derive(Clone)]
+    struct Gene {
+        value: f32,
+        alive: bool,
+    }
+
+    impl Organism for Gene {
+        type Config = ();
+        type Genotype = f32;
+        const GENOTYPE: &'static [u8; 4] = b"TEST";
+        const DEATH_CAUSES: &'static [&'static str] = &[];
+
+        fn random(_: &(), rng: &mut StdRng) -> Self {
+            Gene {
+                value: rng.gen(),
+                alive: true,
+            }
+        }
+
+        fn from_genotype(value: f32, _: &(), _: u64) -> Self {
+            Gene { value, alive: true }
+        }
+
+        fn get_genotype(&self) -> &f32 {
+            &self.value
+        }
+
+        fn get_fitness(&self) -> f32 {
+            self.value
+        }
+
+        fn death_cause(&self) -> Option<&'static str> {
+            None
+        }
+
+        fn distance(&self, other: &Self) -> f32 {
+            (self.value - other.value).abs()
+        }
+
+        fn mutation_rate(&self) -> f64 {
+            0.0
+        }
+
+        fn crossover(&self, b: &Self, _: &Reproduction, _: &mut StdRng) -> Self {
+            Gene::from_genotype((self.value + b.value) / 2.0, &(), 0)
+        }
+
+        fn is_alive(&self) -> bool {
+            self.alive
+        }
+
+        fn reborn(&mut self) {
+            self.alive = true;
+        }
+
+        fn reseed(&mut self, _: u64) {}
+
+        fn kill(&mut self) {
+            self.alive = false;
+        }
+
+        fn tick(&mut self) {
+            self.alive = false;
+        }
+    }
+
+    #[test]
+    fn every_replacement_keeps_the_capacity() {
+        let replacements = [
+            Replacement::Generational,
+            Replacement::SteadyState { replacements: 7 },
+            Replacement::MuPlusLambda { mu: 5 },
+            Replacement::MuCommaLambda { mu: 5 },
+        ];
+        for replacement in replacements {
+            for elitism in [Elitism::Count(0), Elitism::Count(3), Elitism::Fraction(0.5)] {
+                let config = PopulationConfig {
+                    capacity: 21,
+                    elitism,
+                    replacement,
+                    seed: Some(0),
+                    ..PopulationConfig::default()
+                };
+                let mut population: Population<Gene> = Population::new(config, &());
+                for _ in 0..5 {
+                    population.tick();
+                    while !population.is_dead() {
+                        population.tick();
+                    }
+                    population.evolution();
+                    assert_eq!(
+                        population.get_genomes().len(),
+                        population.get_capacity(),
+                        "{:?} with {:?}",
+                        replacement,
+                        elitism
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::fmt::Debug;
+
+use rand::{rngs::StdRng, Rng};
+use rand_distr::{Distribution, Normal};
+
+/// Picks the parents of the next generation.
+pub trait SelectionStrategy: Debug + Send + Sync {
+    /// Returns the indices of `count` parents, two consecutive ones make a pair.
+    ///
+    /// `fitnesses` are sorted from best to worst and never empty.
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize>;
+}
+
+/// Only the best `fraction` of the population may breed, better ones more often.
+#[derive(Debug)]
+pub struct Truncation {
+    pub fraction: f64,
+}
+
+impl SelectionStrategy for Truncation {
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let pool = ((fitnesses.len() as f64 * self.fraction) as usize).clamp(1, fitnesses.len());
+        (0..count)
+            .map(|_| {
+                generate_random_number_tending_towards_smaller(0, pool as u32 - 1, 0.9, rng)
+                    as usize
+            })
+            .collect()
+    }
+}
+
+fn generate_random_number_tending_towards_smaller(
+    n: u32,
+    m: u32,
+    small_likelihood: f64,
+    rng: &mut StdRng,
+) -> u32 {
+    let mean = (n + m) / 2;
+    let std_dev = (m - n) / 4;
+    let normal = Normal::new(mean as f64, std_dev as f64).unwrap();
+
+    let num = normal
+        .sample_iter(&mut *rng)
+        .map(|x| x as u32)
+        .find(|sample| sample >= &n && sample <= &m)
+        .unwrap_or(0u32);
+
+    let rand_num = rng.gen_range(0.0..1.0);
+    if rand_num <= small_likelihood {
+        n + rng.gen_range(0..(num - n).max(1))
+    } else {
+        num + rng.gen_range(0..(m - num + 1).max(1))
+    }
+}
+
+/// The best of `size` organisms drawn at random wins, larger tournaments mean more pressure.
+#[derive(Debug)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl SelectionStrategy for Tournament {
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        (0..count)
+            .map(|_| {
+                // The fitnesses are sorted, so the smallest index is the fittest contestant
+                (0..self.size)
+                    .map(|_| rng.gen_range(0..fitnesses.len()))
+                    .min()
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+}
+
+/// Every organism is picked with a probability proportional to its fitness.
+#[derive(Debug)]
+pub struct Roulette;
+
+impl SelectionStrategy for Roulette {
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let wheel = Wheel::new(proportional_weights(fitnesses));
+        (0..count)
+            .map(|_| wheel.find(rng.gen_range(0.0..1.0)))
+            .collect()
+    }
+}
+
+/// Like [`Roulette`], but with one spin of `count` evenly spaced pointers,
+/// so every organism is picked close to its expected number of times.
+#[derive(Debug)]
+pub struct StochasticUniversalSampling;
+
+impl SelectionStrategy for StochasticUniversalSampling {
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        // Without pointers the spacing would be infinite
+        if count == 0 {
+            return vec![];
+        }
+        let wheel = Wheel::new(proportional_weights(fitnesses));
+        let spacing = 1.0 / count as f64;
+        let start = rng.gen_range(0.0..spacing);
+        let mut parents: Vec<usize> = (0..count)
+            .map(|i| wheel.find(start + i as f64 * spacing))
+            .collect();
+        // The pointers pick in order of fitness, shuffle so the pairs mix good and bad parents
+        for i in (1..parents.len()).rev() {
+            parents.swap(i, rng.gen_range(0..=i));
+        }
+        parents
+    }
+}
+
+/// Linear ranking: the probability depends on the position only, not on how much better an organism is.
+///
+/// The best organism is picked `pressure` times as often as the average one, `pressure` lies in `1..=2`.
+#[derive(Debug)]
+pub struct Rank {
+    pub pressure: f64,
+}
+
+impl SelectionStrategy for Rank {
+    fn select(&self, fitnesses: &[f32], count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let n = fitnesses.len() as f64;
+        let weights = (0..fitnesses.len())
+            .map(|position| {
+                // 0 for the worst organism up to 1 for the best
+                let rank = if n > 1.0 {
+                    1.0 - position as f64 / (n - 1.0)
+                } else {
+                    1.0
+                };
+                2.0 - self.pressure + 2.0 * (self.pressure - 1.0) * rank
+            })
+            .collect();
+        let wheel = Wheel::new(weights);
+        (0..count)
+            .map(|_| wheel.find(rng.gen_range(0.0..1.0)))
+            .collect()
+    }
+}
+
+/// The fitnesses shifted to be non-negative, equal weights if they are all the same.
+fn proportional_weights(fitnesses: &[f32]) -> Vec<f64> {
+    let min = fitnesses
+        .iter()
+        .copied()
+        .fold(f32::INFINITY, f32::min)
+        .min(0.0);
+    let weights: Vec<f64> = fitnesses.iter().map(|f| (f - min) as f64).collect();
+    if weights.iter().sum::<f64>() > 0.0 {
+        weights
+    } else {
+        vec![1.0; fitnesses.len()]
+    }
+}
+
+/// Cumulative weights normalised to 1, a position on the wheel belongs to the organism whose slice contains it.
+struct Wheel {
+    cumulative: Vec<f64>,
+}
+
+impl Wheel {
+    fn new(weights: Vec<f64>) -> Self {
+        let total: f64 = weights.iter().sum();
+        let cumulative = weights
+            .iter()
+            .scan(0.0, |sum, weight| {
+                *sum += weight / total;
+                Some(*sum)
+            })
+            .collect();
+        Wheel { cumulative }
+    }
+
+    /// The organism at `position` in `0..1`
+    fn find(&self, position: f64) -> usize {
+        self.cumulative
+            .partition_point(|end| *end <= position)
+            .min(self.cumulative.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn sus_selects_nothing_for_zero_parents() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let parents = StochasticUniversalSampling.select(&[1.0, 2.0, 3.0], 0, &mut rng);
+        assert!(parents.is_empty());
+    }
+}
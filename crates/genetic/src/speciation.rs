@@ -0,0 +1,227 @@
+use rand::rngs::StdRng;
+
+use crate::organism::Organism;
+use crate::selection::SelectionStrategy;
+
+/// Splits the population into species of similar genotypes that compete mostly among themselves.
+///
+/// Without it a single lineage takes over the whole population within a few generations,
+/// with it a new idea gets a few generations to mature before it has to beat the best organisms.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Speciation {
+    /// Organisms closer than this to the representative of a species belong to it, see [`Organism::distance`]
+    pub threshold: f32,
+    /// A species that did not improve its best fitness for this many generations gets no more children
+    pub stagnation: usize,
+}
+
+/// A group of similar organisms.
+pub struct Species<T> {
+    /// New organisms join the species if they are closer to it than the threshold, the best member of the previous generation
+    representative: T,
+    /// Indices of the members in the population
+    members: Vec<usize>,
+    /// The best fitness the species ever reached
+    best_fitness: f32,
+    /// Generations since the best fitness improved
+    stagnant: usize,
+}
+
+/// Assigns every organism to the first species it is close enough to, or founds a new species with it.
+///
+/// Species without members die out.
+pub fn speciate<T: Organism>(species: &mut Vec<Species<T>>, organisms: &[T], threshold: f32) {
+    species
+        .iter_mut()
+        .for_each(|species| species.members.clear());
+    for (index, organism) in organisms.iter().enumerate() {
+        match species
+            .iter_mut()
+            .find(|species| species.representative.distance(organism) < threshold)
+        {
+            Some(species) => species.members.push(index),
+            None => species.push(Species {
+                representative: organism.clone(),
+                members: vec![index],
+                best_fitness: f32::NEG_INFINITY,
+                stagnant: 0,
+            }),
+        }
+    }
+    species.retain(|species| !species.members.is_empty());
+}
+
+/// Updates the species after the population was sorted from best to worst, call it before [`select_parents`].
+///
+/// `rank` maps the index of an organism at the time of [`speciate`] to its position in `organisms` and `fitnesses`.
+/// The best member of every species becomes its representative.
+pub fn rank<T: Organism>(
+    species: &mut [Species<T>],
+    organisms: &[T],
+    fitnesses: &[f32],
+    rank: &[usize],
+) {
+    for species in species.iter_mut() {
+        species
+            .members
+            .iter_mut()
+            .for_each(|member| *member = rank[*member]);
+        species.members.sort_unstable();
+        let best = species.members[0];
+        if fitnesses[best] > species.best_fitness {
+            species.best_fitness = fitnesses[best];
+            species.stagnant = 0;
+        } else {
+            species.stagnant += 1;
+        }
+        species.representative = organisms[best].clone();
+    }
+}
+
+/// Picks the parents of `children` children, two consecutive ones make a pair and both come from the same species.
+///
+/// `fitnesses` are sorted from best to worst, only the first `breeders` organisms may breed.
+///
+/// Every species gets a share of the children proportional to the sum of the shared fitnesses of its members,
+/// where the fitness of an organism is divided by the size of its species (explicit fitness sharing).
+/// Stagnant species get nothing, except the one of the best organism, so the population never dies out.
+/// Within a species the parents are picked by the `selection` strategy.
+pub fn select_parents<T>(
+    species: &[Species<T>],
+    speciation: &Speciation,
+    fitnesses: &[f32],
+    breeders: usize,
+    children: usize,
+    selection: &dyn SelectionStrategy,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    // Shifted by the worst fitness, so negative fitnesses (e.g. of wall:-5) still make a positive share
+    let worst = fitnesses.iter().copied().fold(f32::INFINITY, f32::min);
+    let breeding = |species: &Species<T>| {
+        let culled = species.stagnant >= speciation.stagnation && species.members[0] != 0;
+        let eligible = species.members.partition_point(|member| *member < breeders);
+        if culled {
+            0
+        } else {
+            eligible
+        }
+    };
+    let mut shares: Vec<f64> = species
+        .iter()
+        .map(|species| {
+            let sum: f64 = species.members[..breeding(species)]
+                .iter()
+                .map(|member| (fitnesses[*member] - worst) as f64)
+                .sum();
+            sum / species.members.len() as f64
+        })
+        .collect();
+    // Everyone is equally bad, so every species that may breed gets the same share
+    if shares.iter().sum::<f64>() <= 0.0 {
+        shares = species
+            .iter()
+            .map(|species| if breeding(species) > 0 { 1.0 } else { 0.0 })
+            .collect();
+    }
+
+    quotas(children, &shares)
+        .into_iter()
+        .zip(species.iter())
+        .flat_map(|(quota, species)| {
+            if quota == 0 {
+                return vec![];
+            }
+            let members = &species.members[..breeding(species)];
+            let member_fitnesses: Vec<f32> =
+                members.iter().map(|member| fitnesses[*member]).collect();
+            selection
+                .select(&member_fitnesses, 2 * quota, rng)
+                .into_iter()
+                .map(|index| members[index])
+                .collect()
+        })
+        .collect()
+}
+
+/// Splits `total` proportionally to `shares` by rounding the running sums, so the quotas add up to `total`.
+///
+/// If all shares are 0 they count the same.
+fn quotas(total: usize, shares: &[f64]) -> Vec<usize> {
+    let sum: f64 = shares.iter().sum();
+    let (weight, sum) = if sum > 0.0 {
+        (None, sum)
+    } else {
+        (Some(1.0), shares.len() as f64)
+    };
+    let mut running = 0.0;
+    let mut handed_out = 0;
+    shares
+        .iter()
+        .map(|share| {
+            running += weight.unwrap_or(*share);
+            let until_here = ((total as f64 * running / sum).round() as usize).min(total);
+            let quota = until_here.saturating_sub(handed_out);
+            handed_out += quota;
+            quota
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::selection::Truncation;
+
+    #[test]
+    fn quotas_add_up_to_the_total() {
+        let cases: [&[f64]; 5] = [
+            &[1.0, 1.0, 1.0],
+            &[0.0, 0.0, 0.0],
+            &[5.0, 0.0, 0.1, 2.5],
+            &[0.3],
+            &[1e-9, 1e9],
+        ];
+        for shares in cases {
+            for total in [0, 1, 7, 100] {
+                let quotas = quotas(total, shares);
+                assert_eq!(quotas.len(), shares.len());
+                assert_eq!(quotas.iter().sum::<usize>(), total, "{:?}", shares);
+            }
+        }
+    }
+
+    #[test]
+    fn stagnation_spares_the_species_of_the_best() {
+        let species = |members: Vec<usize>| Species {
+            representative: (),
+            members,
+            best_fitness: 0.0,
+            stagnant: 3,
+        };
+        // The best organism sits in the middle species
+        let species = [
+            species(vec![1, 4]),
+            species(vec![0, 3, 5]),
+            species(vec![2, 6]),
+        ];
+        let speciation = Speciation {
+            threshold: 1.0,
+            stagnation: 0,
+        };
+        let fitnesses = [10.0, 9.0, 8.0, 7.0, 6.0, 5.0, 4.0];
+        let mut rng = StdRng::seed_from_u64(0);
+        let parents = select_parents(
+            &species,
+            &speciation,
+            &fitnesses,
+            fitnesses.len(),
+            6,
+            &Truncation { fraction: 1.0 },
+            &mut rng,
+        );
+        assert_eq!(parents.len(), 2 * 6);
+        assert!(parents.iter().all(|parent| [0, 3, 5].contains(parent)));
+    }
+}
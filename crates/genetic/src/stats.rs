@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// A summary of one generation, see [`crate::population::Population::stats`].
+pub struct GenerationStats {
+    pub generation: usize,
+    pub min_fitness: f32,
+    pub mean_fitness: f32,
+    pub median_fitness: f32,
+    pub max_fitness: f32,
+    pub stddev_fitness: f32,
+    pub mutation_rate: f64,
+    /// Mean distance between the genotypes, 0 once the population collapsed onto a single genotype
+    pub diversity: f32,
+    /// Wall-clock time the generation took
+    pub duration: Duration,
+    /// How many organisms died of which cause, over all episodes
+    pub deaths: Vec<(&'static str, usize)>,
+    /// Number of species, `None` without speciation
+    pub species: Option<usize>,
+}
+
+impl GenerationStats {
+    /// Computes the fitness statistics, everything else is filled in by the caller.
+    pub fn from_fitnesses(mut fitnesses: Vec<f32>) -> Self {
+        fitnesses.sort_by(f32::total_cmp);
+        let count = fitnesses.len().max(1) as f32;
+        let mean = fitnesses.iter().sum::<f32>() / count;
+        let variance = fitnesses.iter().map(|f| (f - mean).powi(2)).sum::<f32>() / count;
+        let middle = fitnesses.len() / 2;
+        let median = match fitnesses.len() {
+            0 => 0.0,
+            len if len.is_multiple_of(2) => (fitnesses[middle - 1] + fitnesses[middle]) / 2.0,
+            _ => fitnesses[middle],
+        };
+        GenerationStats {
+            generation: 0,
+            min_fitness: fitnesses.first().copied().unwrap_or(0.0),
+            mean_fitness: mean,
+            median_fitness: median,
+            max_fitness: fitnesses.last().copied().unwrap_or(0.0),
+            stddev_fitness: variance.sqrt(),
+            mutation_rate: 0.0,
+            diversity: 0.0,
+            duration: Duration::ZERO,
+            deaths: vec![],
+            species: None,
+        }
+    }
+
+    pub fn deaths_of(&self, cause: &str) -> usize {
+        self.deaths
+            .iter()
+            .find(|(name, _)| *name == cause)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// The named values of the record in a fixed order, the death causes last
+    fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![
+            (String::from("generation"), self.generation.to_string()),
+            (String::from("min_fitness"), self.min_fitness.to_string()),
+            (String::from("mean_fitness"), self.mean_fitness.to_string()),
+            (
+                String::from("median_fitness"),
+                self.median_fitness.to_string(),
+            ),
+            (String::from("max_fitness"), self.max_fitness.to_string()),
+            (
+                String::from("stddev_fitness"),
+                self.stddev_fitness.to_string(),
+            ),
+            (
+                String::from("mutation_rate"),
+                self.mutation_rate.to_string(),
+            ),
+            (String::from("diversity"), self.diversity.to_string()),
+            (
+                String::from("species"),
+                self.species
+                    .map_or(String::new(), |species| species.to_string()),
+            ),
+            (
+                String::from("seconds"),
+                self.duration.as_secs_f64().to_string(),
+            ),
+        ];
+        fields.extend(
+            self.deaths
+                .iter()
+                .map(|(cause, count)| (format!("deaths_{}", cause), count.to_string())),
+        );
+        fields
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Format {
+    Csv,
+    JsonLines,
+}
+
+/// Appends one line per generation to a CSV or JSON Lines file, so runs can be plotted offline.
+///
+/// I wrote the few lines of CSV and JSON by hand instead of pulling in serde, like the checkpoints.
+pub struct StatsLog {
+    writer: BufWriter<File>,
+    format: Format,
+    wrote_header: bool,
+}
+
+impl StatsLog {
+    /// Creates the log, the format follows from the extension: `.csv` or `.jsonl`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("csv") => Format::Csv,
+            Some("jsonl") => Format::JsonLines,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} must end with .csv or .jsonl", path.display()),
+                ))
+            }
+        };
+        Ok(StatsLog {
+            writer: BufWriter::new(File::create(path)?),
+            format,
+            wrote_header: false,
+        })
+    }
+
+    /// Writes the record and flushes it, so the file is usable while the run goes on.
+    pub fn write(&mut self, stats: &GenerationStats) -> io::Result<()> {
+        let fields = stats.fields();
+        match self.format {
+            Format::Csv => {
+                if !self.wrote_header {
+                    let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                    writeln!(self.writer, "{}", names.join(","))?;
+                    self.wrote_header = true;
+                }
+                let values: Vec<&str> = fields.iter().map(|(_, value)| value.as_str()).collect();
+                writeln!(self.writer, "{}", values.join(","))?;
+            }
+            Format::JsonLines => {
+                // Every value is a number or empty, JSON has no NaN or infinity though
+                let members: Vec<String> = fields
+                    .iter()
+                    .map(|(name, value)| match value.parse::<f64>() {
+                        Ok(number) if number.is_finite() => format!("\"{}\":{}", name, value),
+                        _ => format!("\"{}\":null", name),
+                    })
+                    .collect();
+                writeln!(self.writer, "{{{}}}", members.join(","))?;
+            }
+        }
+        self.writer.flush()
+    }
+}
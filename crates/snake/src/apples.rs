@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::board::Board;
+use super::point::Point;
+
+/// Decides where new apples appear.
+#[derive(Clone, Debug, Default)]
+pub enum SpawnPolicy {
+    /// A random free cell drawn from the rng of the snake
+    #[default]
+    Random,
+    /// A random free cell drawn from a stream with a fixed seed,
+    /// so every snake is offered the same apples as long as those cells are free
+    Seeded(u64),
+    /// The given cells in order, starting over at the end
+    ///
+    /// Occupied cells and cells outside the board are skipped.
+    /// If none of the listed cells is available a random free cell is used.
+    Fixed(Arc<[Point]>),
+}
+
+impl SpawnPolicy {
+    /// Creates the spawner for a new game.
+    pub fn spawner(&self) -> AppleSpawner {
+        match self {
+            SpawnPolicy::Random => AppleSpawner::Random,
+            SpawnPolicy::Seeded(seed) => {
+                AppleSpawner::Seeded(Box::new(StdRng::seed_from_u64(*seed)))
+            }
+            SpawnPolicy::Fixed(cells) => AppleSpawner::Fixed {
+                cells: cells.clone(),
+                next: 0,
+            },
+        }
+    }
+}
+
+/// The state of a [`SpawnPolicy`] during one game.
+#[derive(Clone)]
+pub enum AppleSpawner {
+    Random,
+    Seeded(Box<StdRng>),
+    Fixed { cells: Arc<[Point]>, next: usize },
+}
+
+impl AppleSpawner {
+    /// Places an apple on a cell that is not in `occupied`.
+    ///
+    /// Returns `None` if the whole field is occupied.
+    pub fn spawn(
+        &mut self,
+        board: &Board,
+        occupied: &HashSet<Point>,
+        rng: &mut StdRng,
+    ) -> Option<Point> {
+        match self {
+            AppleSpawner::Random => random_free_cell(board, occupied, rng),
+            AppleSpawner::Seeded(seeded_rng) => random_free_cell(board, occupied, seeded_rng),
+            AppleSpawner::Fixed { cells, next } => {
+                let found = (0..cells.len())
+                    .map(|offset| (*next + offset) % cells.len())
+                    .find(|&index| {
+                        board.contains(cells[index]) && !occupied.contains(&cells[index])
+                    });
+                match found {
+                    Some(index) => {
+                        *next = index + 1;
+                        Some(cells[index])
+                    }
+                    None => random_free_cell(board, occupied, rng),
+                }
+            }
+        }
+    }
+}
+
+/// Draws uniformly from the free cells only, so an apple never lands on the snake.
+fn random_free_cell(board: &Board, occupied: &HashSet<Point>, rng: &mut StdRng) -> Option<Point> {
+    let free = board
+        .area()
+        .checked_sub(occupied.len())
+        .filter(|free| *free > 0)?;
+    board
+        .cells()
+        .filter(|cell| !occupied.contains(cell))
+        .nth(rng.gen_range(0..free))
+}
@@ -0,0 +1,47 @@
+use rand::Rng;
+
+use super::point::Point;
+
+/// The field a snake plays on. Cells range from `0,0` to `width - 1,height - 1`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Board {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board {
+            width: 32,
+            height: 32,
+        }
+    }
+}
+
+impl Board {
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
+    }
+
+    /// Number of cells on the board
+    pub fn area(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    /// Every cell of the board, row by row
+    pub fn cells(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point { x, y }))
+    }
+
+    /// Creates a point with random coordinates on the board.
+    ///
+    /// This used to be the `Default` implementation of `Point` using `thread_rng`.
+    /// Taking the rng as a parameter makes runs reproducible, and `Point::default() == Point::default()` was surprising anyways.
+    pub fn random_point(&self, rng: &mut impl Rng) -> Point {
+        Point {
+            x: rng.gen_range(0..self.width),
+            y: rng.gen_range(0..self.height),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use genetic::brain::Brain;
+use genetic::genome::Topology;
+
+use super::apples::SpawnPolicy;
+use super::board::Board;
+use super::fitness::Fitness;
+use super::sensors::SensorSuite;
+
+/// Everything that defines the game a snake plays.
+#[derive(Clone, Debug)]
+pub struct SnakeConfig {
+    pub board: Board,
+    pub rules: Rules,
+    pub sensors: SensorSuite,
+    /// The hidden layers of the network, its inputs follow from the sensors
+    pub topology: Topology,
+    pub spawn_policy: SpawnPolicy,
+    /// Number of apples on the field at the same time
+    pub apple_count: usize,
+    /// How well a snake played, this is what the evolution optimises
+    pub fitness: Fitness,
+    /// Every snake records its game, so the best one can be saved as a [`super::Replay`]
+    pub record: bool,
+}
+
+impl Default for SnakeConfig {
+    fn default() -> Self {
+        SnakeConfig {
+            board: Board::default(),
+            rules: Rules::default(),
+            sensors: SensorSuite::default(),
+            topology: Topology::default(),
+            spawn_policy: SpawnPolicy::default(),
+            apple_count: 1,
+            fitness: Fitness::default(),
+            record: false,
+        }
+    }
+}
+
+impl SnakeConfig {
+    /// Checks that a loaded network fits the sensors and topology of this config.
+    pub fn check_brain(&self, brain: &impl Brain) -> Result<(), String> {
+        brain.check_shape(self.sensors.len(), super::DIRECTIONS.len(), &self.topology)
+    }
+}
+
+/// The rules that decide when a snake dies.
+#[derive(Clone, Copy, Debug)]
+pub struct Rules {
+    /// Die when the head runs into the body
+    ///
+    /// Turning this off restores the original game, which is useful for comparison experiments.
+    pub self_collision: bool,
+    /// Die after this many moves without eating an apple
+    pub starvation_moves: i32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            self_collision: true,
+            starvation_moves: 100,
+        }
+    }
+}
@@ -1,20 +1,24 @@
 use super::Point;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
 pub enum Direction {
+    #[default]
     Up,
     Down,
     Left,
     Right,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::Up
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
     }
-}
 
-impl Direction {
     pub fn movement_vector(&self) -> Point {
         match self {
             Direction::Up => Point { x: 0, y: -1 },
@@ -0,0 +1,73 @@
+use super::DeathCause;
+
+/// A measurement of a finished (or running) game that can be part of the fitness.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FitnessTerm {
+    /// The length of the snake, the original fitness
+    Length,
+    /// Number of apples eaten
+    Apples,
+    /// Number of moves survived
+    Steps,
+    /// Moves per eaten apple (all moves if none was eaten), use a negative weight to punish slow snakes
+    StepsPerApple,
+    /// 1 if the snake ran into a wall, use a negative weight to punish it
+    WallDeath,
+    /// `2^apples`, so every apple counts more than all before it
+    ExponentialLength,
+}
+
+/// What a fitness term is computed from.
+pub struct GameSummary {
+    pub length: usize,
+    pub steps: usize,
+    pub death_cause: Option<DeathCause>,
+}
+
+impl FitnessTerm {
+    /// Caps the exponent of [`FitnessTerm::ExponentialLength`], so the fitness stays finite on big boards
+    const MAX_EXPONENT: i32 = 100;
+
+    fn value(self, game: &GameSummary) -> f32 {
+        let apples = game.length - 1;
+        match self {
+            FitnessTerm::Length => game.length as f32,
+            FitnessTerm::Apples => apples as f32,
+            FitnessTerm::Steps => game.steps as f32,
+            FitnessTerm::StepsPerApple => game.steps as f32 / apples.max(1) as f32,
+            FitnessTerm::WallDeath => {
+                if game.death_cause == Some(DeathCause::Wall) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            FitnessTerm::ExponentialLength => 2f32.powi((apples as i32).min(Self::MAX_EXPONENT)),
+        }
+    }
+}
+
+/// The fitness of a snake as a weighted sum of terms.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fitness {
+    pub terms: Vec<(FitnessTerm, f32)>,
+}
+
+impl Default for Fitness {
+    /// Just the length
+    fn default() -> Self {
+        Fitness {
+            terms: vec![(FitnessTerm::Length, 1.0)],
+        }
+    }
+}
+
+impl Fitness {
+    pub fn evaluate(&self, game: &GameSummary) -> f32 {
+        self.terms
+            .iter()
+            .map(|(term, weight)| weight * term.value(game))
+            // Starts at +0, `sum` would start at -0 and report a snake that never hit a wall with `wall:-5` as -0
+            .fold(0.0, |sum, value| sum + value)
+    }
+}
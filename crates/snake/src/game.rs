@@ -0,0 +1,285 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use genetic::environment::{DiscreteActions, Environment, Step};
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::apples::AppleSpawner;
+use super::config::SnakeConfig;
+use super::direction::Direction;
+use super::fitness::GameSummary;
+use super::point::Point;
+use super::replay::Replay;
+use super::sensors::Surroundings;
+use super::{DeathCause, DIRECTIONS};
+
+/// A game of snake, whoever plays it: a network through [`super::Snake`] or a human in the terminal.
+#[derive(Clone)]
+pub struct SnakeGame {
+    config: Arc<SnakeConfig>,
+    /// Every game has its own stream, so the apples do not depend on the order in which rayon ticks the snakes
+    rng: StdRng,
+
+    cells: VecDeque<Point>,
+    apples: Vec<Point>,
+    spawner: AppleSpawner,
+    direction: Direction,
+    alive: bool,
+    /// The snake filled the whole field
+    won: bool,
+    death_cause: Option<DeathCause>,
+    /// Moves since the last apple
+    moves_made: i32,
+    /// Moves since the game started
+    steps: usize,
+    /// The game so far, if the config asks for recording
+    replay: Option<Replay>,
+}
+
+impl SnakeGame {
+    /// Starts a game, `seed` determines where the snake starts and where the apples appear.
+    pub fn new(config: Arc<SnakeConfig>, seed: u64) -> SnakeGame {
+        let mut game = SnakeGame {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            cells: VecDeque::new(),
+            apples: Vec::new(),
+            spawner: AppleSpawner::Random,
+            direction: Direction::Up,
+            alive: true,
+            won: false,
+            death_cause: None,
+            moves_made: 0,
+            steps: 0,
+            replay: None,
+        };
+        game.reset();
+        game
+    }
+
+    pub fn config(&self) -> &Arc<SnakeConfig> {
+        &self.config
+    }
+
+    /// Restart the random number stream of the game, the next [`Environment::reset`] draws from it
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Let the snake starve on its next move
+    pub fn kill(&mut self) {
+        self.moves_made = self.config.rules.starvation_moves;
+    }
+
+    /// How well the snake played so far, see [`SnakeConfig::fitness`]
+    pub fn get_fitness(&self) -> f32 {
+        self.config.fitness.evaluate(&GameSummary {
+            length: self.get_length(),
+            steps: self.steps,
+            death_cause: self.death_cause,
+        })
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn get_cells(&self) -> &VecDeque<Point> {
+        &self.cells
+    }
+
+    pub fn get_apples(&self) -> &[Point] {
+        &self.apples
+    }
+
+    /// The apple closest to the head, or the head itself if there is no apple left.
+    pub fn get_nearest_apple(&self) -> Point {
+        let head = self.cells[0];
+        self.apples
+            .iter()
+            .copied()
+            .min_by_key(|apple| (apple.x - head.x).abs() + (apple.y - head.y).abs())
+            .unwrap_or(head)
+    }
+
+    fn spawn_apple(&mut self) {
+        let occupied: HashSet<Point> = self.cells.iter().chain(&self.apples).copied().collect();
+        if let Some(apple) = self
+            .spawner
+            .spawn(&self.config.board, &occupied, &mut self.rng)
+        {
+            self.apples.push(apple);
+            if let Some(replay) = &mut self.replay {
+                replay.apples.push(apple);
+            }
+        }
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn get_length(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn get_death_cause(&self) -> Option<DeathCause> {
+        self.death_cause
+    }
+
+    /// The recorded game, only available if [`SnakeConfig::record`] is set
+    pub fn get_replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn has_won(&self) -> bool {
+        self.won
+    }
+}
+
+impl Environment for SnakeGame {
+    type Action = Direction;
+
+    fn reset(&mut self) {
+        self.cells = VecDeque::from([self.config.board.random_point(&mut self.rng)]);
+        self.direction = Direction::Up;
+        self.alive = true;
+        self.won = false;
+        self.death_cause = None;
+        self.moves_made = 0;
+        self.steps = 0;
+        self.spawner = self.config.spawn_policy.spawner();
+        self.apples.clear();
+        self.replay = self.config.record.then(|| Replay {
+            board: self.config.board,
+            start: self.cells[0],
+            apple_count: 0,
+            apples: vec![],
+            moves: vec![],
+            death_cause: None,
+        });
+        (0..self.config.apple_count).for_each(|_| self.spawn_apple());
+        // A crowded board may have room for fewer apples than asked for
+        if let Some(replay) = &mut self.replay {
+            replay.apple_count = self.apples.len();
+        }
+    }
+
+    fn observe(&self) -> Vec<f32> {
+        self.config.sensors.observe(&Surroundings {
+            board: &self.config.board,
+            cells: &self.cells,
+            apple: self.get_nearest_apple(),
+            apples: &self.apples,
+            direction: self.direction,
+        })
+    }
+
+    /// Moves the snake one cell in `direction`, the reward is the change of its fitness.
+    fn step(&mut self, direction: Direction) -> Step {
+        // I actually tried to refactor the for loop into an iterator, but with a Vec it requires a streaming/lending iterator I think.
+        // While I tried to use that I somehow changed the datastructure to a deque which turns out to not need the loop at all.
+        // If you are really motivated I can really recommend looking into rust iterators as they are quite powerful.
+        // Iterators also usually result in more efficient code than loops, because the compiler is better at optimizing them.
+        // The rust course at my university had some great workshop exercises on iterators, I will attach them to my email.
+
+        let fitness_before = self.get_fitness();
+        self.direction = direction;
+        let new_head = self.cells[0] + self.direction.movement_vector();
+
+        // The tail leaves its cell in the same tick, so the head may move onto it unless the snake grows.
+        let eaten = self.apples.iter().position(|apple| *apple == new_head);
+        match eaten {
+            Some(index) => {
+                self.apples.swap_remove(index);
+                self.moves_made = 0;
+            }
+            None => {
+                self.cells.pop_back();
+            }
+        }
+
+        let rules = &self.config.rules;
+        self.death_cause = if self.moves_made >= rules.starvation_moves {
+            Some(DeathCause::Starvation)
+        } else if !self.config.board.contains(new_head) {
+            Some(DeathCause::Wall)
+        } else if rules.self_collision && self.cells.contains(&new_head) {
+            Some(DeathCause::SelfCollision)
+        } else {
+            None
+        };
+        self.alive = self.death_cause.is_none();
+        if let Some(replay) = &mut self.replay {
+            replay.moves.push(self.direction);
+            replay.death_cause = self.death_cause;
+        }
+
+        self.cells.push_front(new_head);
+        self.moves_made += 1;
+        self.steps += 1;
+
+        if self.alive && eaten.is_some() {
+            self.spawn_apple();
+        }
+        if self.cells.len() == self.config.board.area() {
+            self.won = true;
+            self.alive = false;
+        }
+        Step {
+            reward: self.get_fitness() - fitness_before,
+            done: !self.alive,
+        }
+    }
+}
+
+impl DiscreteActions for SnakeGame {
+    const ACTIONS: &'static [Direction] = &DIRECTIONS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game with a snake of length 4 curled into a square, its head at (2, 1) right next to its tail at (1, 1)
+    fn curled_up(config: SnakeConfig, apples: Vec<Point>) -> SnakeGame {
+        let mut game = SnakeGame::new(Arc::new(config), 0);
+        game.cells = VecDeque::from([
+            Point { x: 2, y: 1 },
+            Point { x: 2, y: 2 },
+            Point { x: 1, y: 2 },
+            Point { x: 1, y: 1 },
+        ]);
+        game.apples = apples;
+        game
+    }
+
+    #[test]
+    fn head_may_follow_the_tail() {
+        let mut game = curled_up(SnakeConfig::default(), vec![Point { x: 9, y: 9 }]);
+        let step = game.step(Direction::Left);
+        assert!(!step.done);
+        assert_eq!(game.get_cells()[0], Point { x: 1, y: 1 });
+        assert_eq!(game.get_length(), 4);
+    }
+
+    #[test]
+    fn head_hits_the_tail_while_growing() {
+        let mut game = curled_up(SnakeConfig::default(), vec![Point { x: 1, y: 1 }]);
+        let step = game.step(Direction::Left);
+        assert!(step.done);
+        assert_eq!(game.get_death_cause(), Some(DeathCause::SelfCollision));
+    }
+
+    #[test]
+    fn body_is_harmless_without_self_collision() {
+        let mut config = SnakeConfig::default();
+        config.rules.self_collision = false;
+        let mut game = curled_up(config, vec![Point { x: 9, y: 9 }]);
+        assert!(!game.step(Direction::Down).done);
+
+        let mut game = curled_up(SnakeConfig::default(), vec![Point { x: 9, y: 9 }]);
+        assert!(game.step(Direction::Down).done);
+        assert_eq!(game.get_death_cause(), Some(DeathCause::SelfCollision));
+    }
+}
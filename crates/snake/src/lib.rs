@@ -0,0 +1,156 @@
+//! The game of snake, played by a [`Snake`] organism that the `genetic` crate evolves.
+
+mod apples;
+mod board;
+mod config;
+mod direction;
+mod fitness;
+mod game;
+mod point;
+mod replay;
+mod sensors;
+
+use std::sync::Arc;
+
+use genetic::brain::Brain;
+use genetic::environment::{Environment, Policy};
+pub use genetic::genome::Genome;
+use genetic::organism::{Organism, Reproduction};
+use rand::{rngs::StdRng, Rng};
+
+pub use crate::apples::{AppleSpawner, SpawnPolicy};
+pub use crate::board::Board;
+pub use crate::config::SnakeConfig;
+pub use crate::direction::Direction;
+pub use crate::fitness::{Fitness, FitnessTerm};
+pub use crate::game::SnakeGame;
+pub use crate::point::Point;
+pub use crate::replay::Replay;
+pub use crate::sensors::Sensor;
+
+/// The direction each output of the neural network stands for
+pub const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
+/// Why a snake died.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Starvation,
+}
+
+impl DeathCause {
+    pub fn name(self) -> &'static str {
+        match self {
+            DeathCause::Wall => "wall",
+            DeathCause::SelfCollision => "self_collision",
+            DeathCause::Starvation => "starvation",
+        }
+    }
+}
+
+/// A snake whose moves are decided by a `B`, the layered [`Genome`] unless said otherwise.
+///
+/// The organism is only the pairing of the network with a [`SnakeGame`], the game itself does not know who plays it.
+#[derive(Clone)]
+pub struct Snake<B = Genome> {
+    genome: B,
+    game: SnakeGame,
+}
+
+impl<B: Brain> Organism for Snake<B> {
+    type Config = Arc<SnakeConfig>;
+    type Genotype = B;
+
+    const GENOTYPE: &'static [u8; 4] = B::MAGIC;
+
+    fn random(config: &Arc<SnakeConfig>, rng: &mut StdRng) -> Self {
+        let genome = B::random(
+            config.sensors.len(),
+            DIRECTIONS.len(),
+            &config.topology,
+            rng,
+        );
+        Snake::new(genome, config.clone(), rng.gen())
+    }
+
+    fn from_genotype(genome: B, config: &Arc<SnakeConfig>, seed: u64) -> Self {
+        Snake::new(genome, config.clone(), seed)
+    }
+
+    fn get_genotype(&self) -> &B {
+        &self.genome
+    }
+
+    fn get_fitness(&self) -> f32 {
+        self.game.get_fitness()
+    }
+
+    const DEATH_CAUSES: &'static [&'static str] = &["wall", "self_collision", "starvation"];
+
+    fn death_cause(&self) -> Option<&'static str> {
+        self.game.get_death_cause().map(DeathCause::name)
+    }
+
+    fn distance(&self, other: &Snake<B>) -> f32 {
+        self.genome.distance(&other.genome)
+    }
+
+    fn mutation_rate(&self) -> f64 {
+        self.genome.mutation_rate()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.game.is_alive()
+    }
+
+    fn reborn(&mut self) {
+        self.game.reset();
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.game.reseed(seed);
+    }
+
+    fn kill(&mut self) {
+        self.game.kill();
+    }
+
+    fn tick(&mut self) {
+        let direction = Policy::<SnakeGame>::act(&self.genome, &self.game);
+        self.game.step(direction);
+    }
+
+    fn crossover(&self, b: &Snake<B>, reproduction: &Reproduction, rng: &mut StdRng) -> Snake<B> {
+        Snake::new(
+            self.genome.crossover(&b.genome, reproduction, rng),
+            self.game.config().clone(),
+            rng.gen(),
+        )
+    }
+}
+
+impl<B: Brain> Snake<B> {
+    /// Creates a living snake, `seed` determines where it starts and where the apples appear.
+    pub fn new(genome: B, config: Arc<SnakeConfig>, seed: u64) -> Snake<B> {
+        Snake {
+            genome,
+            game: SnakeGame::new(config, seed),
+        }
+    }
+
+    /// The game the snake plays
+    pub fn game(&self) -> &SnakeGame {
+        &self.game
+    }
+
+    /// The activations of the output layer for the current state, one per entry of [`DIRECTIONS`]
+    pub fn get_outputs(&self) -> Vec<f32> {
+        self.genome.forward(&self.game.observe())
+    }
+}
@@ -0,0 +1,32 @@
+use std::ops::Add;
+
+/// A point in a 2D space.
+//
+// It is usually a good idea to derive everything that makes sense for a datatype.
+// https://users.rust-lang.org/t/what-traits-should-i-normally-derive/484/9
+//
+// For libraries it is recommended to eagerly derive or implement common types.
+// https://rust-lang.github.io/api-guidelines/interoperability.html#c-common-traits
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+// Implementing the Add trait is more ergonomic than implementing a custom add function.
+//
+// Interestingly the example used for the Add trait is also a Point, so this is literally the example from the docs.
+// https://doc.rust-lang.org/std/ops/trait.Add.html#addable-points
+//
+// Maybe it would be better to implement Add for &Point so we can also add refs.
+// I have not tried it but I think it should work.
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use genetic::persistence::{self, invalid_data, Persist};
+
+use super::board::Board;
+use super::direction::Direction;
+use super::point::Point;
+use super::DeathCause;
+
+const REPLAY_MAGIC: &[u8; 4] = b"SNKR";
+const REPLAY_VERSION: u32 = 1;
+
+/// Everything needed to play a game back: where it started, where the apples appeared and every move.
+///
+/// Nothing random is stored, so a replay stays valid no matter how the snake or the apples were chosen.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub board: Board,
+    pub start: Point,
+    /// Number of apples on the field at the start
+    pub apple_count: usize,
+    /// Every apple in the order it appeared, the first `apple_count` were there from the start
+    pub apples: Vec<Point>,
+    pub moves: Vec<Direction>,
+    pub death_cause: Option<DeathCause>,
+}
+
+/// The state of a replayed game after some moves.
+pub struct Frame {
+    pub cells: VecDeque<Point>,
+    pub apples: Vec<Point>,
+}
+
+impl Replay {
+    /// Plays the moves with the rules of [`super::SnakeGame`] and returns the state before the first and after every move.
+    pub fn frames(&self) -> Vec<Frame> {
+        let mut cells = VecDeque::from([self.start]);
+        let initial = self.apple_count.min(self.apples.len());
+        let mut apples = self.apples[..initial].to_vec();
+        let mut next_apple = initial;
+
+        let mut frames = vec![Frame {
+            cells: cells.clone(),
+            apples: apples.clone(),
+        }];
+        for (index, direction) in self.moves.iter().enumerate() {
+            let new_head = cells[0] + direction.movement_vector();
+            match apples.iter().position(|apple| *apple == new_head) {
+                Some(eaten) => {
+                    apples.swap_remove(eaten);
+                    // The last move may be the deadly one, dead snakes get no new apple
+                    let died = index + 1 == self.moves.len() && self.death_cause.is_some();
+                    if !died && next_apple < self.apples.len() {
+                        apples.push(self.apples[next_apple]);
+                        next_apple += 1;
+                    }
+                }
+                None => {
+                    cells.pop_back();
+                }
+            }
+            cells.push_front(new_head);
+            frames.push(Frame {
+                cells: cells.clone(),
+                apples: apples.clone(),
+            });
+        }
+        frames
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        persistence::save_file(path, REPLAY_MAGIC, REPLAY_VERSION, |writer| {
+            self.write_to(writer)
+        })
+    }
+
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let mut reader = persistence::open_file(path, REPLAY_MAGIC, REPLAY_VERSION)?;
+        Replay::read_from(&mut reader)
+    }
+}
+
+impl Persist for Point {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.x.write_to(writer)?;
+        self.y.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(Point {
+            x: i32::read_from(reader)?,
+            y: i32::read_from(reader)?,
+        })
+    }
+}
+
+impl Persist for Direction {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let code: u8 = match self {
+            Direction::Up => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Right => 3,
+        };
+        code.write_to(writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(Direction::Up),
+            1 => Ok(Direction::Down),
+            2 => Ok(Direction::Left),
+            3 => Ok(Direction::Right),
+            code => Err(invalid_data(format!("Unknown direction {}", code))),
+        }
+    }
+}
+
+/// Stores the death cause as a code, 0 means the snake did not die (it won or the game was cut short).
+///
+/// A free function, since `Persist` can't be implemented for an `Option` outside of the genetic crate.
+fn write_death_cause(death_cause: Option<DeathCause>, writer: &mut impl Write) -> io::Result<()> {
+    let code: u8 = match death_cause {
+        None => 0,
+        Some(DeathCause::Wall) => 1,
+        Some(DeathCause::SelfCollision) => 2,
+        Some(DeathCause::Starvation) => 3,
+    };
+    code.write_to(writer)
+}
+
+fn read_death_cause(reader: &mut impl Read) -> io::Result<Option<DeathCause>> {
+    match u8::read_from(reader)? {
+        0 => Ok(None),
+        1 => Ok(Some(DeathCause::Wall)),
+        2 => Ok(Some(DeathCause::SelfCollision)),
+        3 => Ok(Some(DeathCause::Starvation)),
+        code => Err(invalid_data(format!("Unknown death cause {}", code))),
+    }
+}
+
+impl Persist for Replay {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.board.width.write_to(writer)?;
+        self.board.height.write_to(writer)?;
+        self.start.write_to(writer)?;
+        (self.apple_count as u32).write_to(writer)?;
+        self.apples.write_to(writer)?;
+        self.moves.write_to(writer)?;
+        write_death_cause(self.death_cause, writer)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let replay = Replay {
+            board: Board {
+                width: i32::read_from(reader)?,
+                height: i32::read_from(reader)?,
+            },
+            start: Point::read_from(reader)?,
+            apple_count: u32::read_from(reader)? as usize,
+            apples: Vec::read_from(reader)?,
+            moves: Vec::read_from(reader)?,
+            death_cause: read_death_cause(reader)?,
+        };
+        if replay.board.width < 1 || replay.board.height < 1 || !replay.board.contains(replay.start)
+        {
+            return Err(invalid_data("The replay starts outside its board"));
+        }
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use genetic::environment::Environment;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    use super::*;
+    use crate::{SnakeConfig, SnakeGame, DIRECTIONS};
+
+    #[test]
+    fn saved_game_plays_back_like_the_live_one() {
+        let config = SnakeConfig {
+            record: true,
+            ..SnakeConfig::default()
+        };
+        for seed in 0..20 {
+            let mut game = SnakeGame::new(Arc::new(config.clone()), seed);
+            let mut rng = StdRng::seed_from_u64(seed);
+            while !game.step(*DIRECTIONS.choose(&mut rng).unwrap()).done {}
+
+            let mut bytes = vec![];
+            game.get_replay().unwrap().write_to(&mut bytes).unwrap();
+            let replay = Replay::read_from(&mut bytes.as_slice()).unwrap();
+            assert_eq!(replay.death_cause, game.get_death_cause());
+
+            let last = replay.frames().pop().unwrap();
+            assert_eq!(&last.cells, game.get_cells());
+            assert_eq!(last.apples, game.get_apples());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use super::board::Board;
+use super::direction::Direction;
+use super::point::Point;
+
+/// A group of values a snake can perceive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sensor {
+    /// The original six inputs: distances to the four walls and the offset to the nearest apple
+    Legacy,
+    /// Distance to the wall, the body and an apple along 8 directions, 24 values
+    Rays,
+    /// The direction the snake moves in, one-hot
+    Heading,
+    /// The direction the tail moves in, one-hot
+    TailDirection,
+}
+
+/// The 8 ray directions, clockwise starting upwards
+const RAYS: [Point; 8] = [
+    Point { x: 0, y: -1 },
+    Point { x: 1, y: -1 },
+    Point { x: 1, y: 0 },
+    Point { x: 1, y: 1 },
+    Point { x: 0, y: 1 },
+    Point { x: -1, y: 1 },
+    Point { x: -1, y: 0 },
+    Point { x: -1, y: -1 },
+];
+
+impl Sensor {
+    /// Number of values this sensor produces
+    pub fn value_count(self) -> usize {
+        match self {
+            Sensor::Legacy => 6,
+            Sensor::Rays => RAYS.len() * 3,
+            Sensor::Heading | Sensor::TailDirection => 4,
+        }
+    }
+}
+
+/// What a snake sees of the game.
+pub struct Surroundings<'a> {
+    pub board: &'a Board,
+    pub cells: &'a VecDeque<Point>,
+    pub apple: Point,
+    pub apples: &'a [Point],
+    pub direction: Direction,
+}
+
+/// The sensors a snake perceives its surroundings with. The network gets one input per value.
+#[derive(Clone, Debug)]
+pub struct SensorSuite {
+    pub sensors: Vec<Sensor>,
+    /// Scale the values to roughly `-1..1`, so they do not depend on the board size
+    ///
+    /// Distances along rays become `1 / distance`, so close things give strong signals and nothing gives 0.
+    pub normalise: bool,
+}
+
+impl Default for SensorSuite {
+    fn default() -> Self {
+        SensorSuite {
+            sensors: vec![Sensor::Legacy],
+            normalise: false,
+        }
+    }
+}
+
+impl SensorSuite {
+    /// Number of values the suite produces, i.e. the input size of the network
+    pub fn len(&self) -> usize {
+        self.sensors.iter().map(|sensor| sensor.value_count()).sum()
+    }
+
+    pub fn observe(&self, surroundings: &Surroundings) -> Vec<f32> {
+        let mut values = Vec::with_capacity(self.len());
+        self.sensors.iter().for_each(|sensor| match sensor {
+            Sensor::Legacy => self.legacy(surroundings, &mut values),
+            Sensor::Rays => self.rays(surroundings, &mut values),
+            Sensor::Heading => one_hot(surroundings.direction, &mut values),
+            Sensor::TailDirection => one_hot(tail_direction(surroundings), &mut values),
+        });
+        values
+    }
+
+    fn legacy(&self, surroundings: &Surroundings, values: &mut Vec<f32>) {
+        let head = surroundings.cells[0];
+        let board = surroundings.board;
+        let (width, height) = if self.normalise {
+            (board.width as f32, board.height as f32)
+        } else {
+            (1.0, 1.0)
+        };
+        values.extend([
+            head.y as f32 / height,
+            (board.height - head.y) as f32 / height,
+            head.x as f32 / width,
+            (board.width - head.x) as f32 / width,
+            (head.x - surroundings.apple.x) as f32 / width,
+            (head.y - surroundings.apple.y) as f32 / height,
+        ]);
+    }
+
+    /// Walks from the head in every ray direction and reports the distance to the wall
+    /// and to the first body cell and apple on the way, 0 if there is none.
+    fn rays(&self, surroundings: &Surroundings, values: &mut Vec<f32>) {
+        let head = surroundings.cells[0];
+        for step in RAYS {
+            let mut wall = 0;
+            let mut body = None;
+            let mut apple = None;
+            let mut cell = head + step;
+            while surroundings.board.contains(cell) {
+                wall += 1;
+                if body.is_none() && surroundings.cells.contains(&cell) {
+                    body = Some(wall);
+                }
+                if apple.is_none() && surroundings.apples.contains(&cell) {
+                    apple = Some(wall);
+                }
+                cell = cell + step;
+            }
+            // Leaving the board counts as one more step, so a wall right next to the head is 1 away
+            values.extend(
+                [Some(wall + 1), body, apple].map(|distance| match distance {
+                    Some(distance) if self.normalise => 1.0 / distance as f32,
+                    Some(distance) => distance as f32,
+                    None => 0.0,
+                }),
+            );
+        }
+    }
+}
+
+fn one_hot(direction: Direction, values: &mut Vec<f32>) {
+    values.extend(
+        [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .map(|candidate| if candidate == direction { 1.0 } else { 0.0 }),
+    );
+}
+
+/// The direction from the last cell towards the one before it, or the heading for a snake of length 1.
+fn tail_direction(surroundings: &Surroundings) -> Direction {
+    let cells = surroundings.cells;
+    if cells.len() < 2 {
+        return surroundings.direction;
+    }
+    let tail = cells[cells.len() - 1];
+    let before_tail = cells[cells.len() - 2];
+    match (before_tail.x - tail.x, before_tail.y - tail.y) {
+        (0, -1) => Direction::Up,
+        (0, 1) => Direction::Down,
+        (-1, 0) => Direction::Left,
+        _ => Direction::Right,
+    }
+}
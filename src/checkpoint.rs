@@ -0,0 +1,74 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use genetic::brain::Brain;
+use genetic::organism::Organism;
+use genetic::persistence::invalid_data;
+use genetic::population::{Population, PopulationConfig};
+use snake::{Snake, SnakeConfig};
+
+/// Where a run resumes from and where it stores its progress.
+#[derive(Clone, Default)]
+pub struct Checkpoint {
+    pub load: Option<PathBuf>,
+    pub save: Option<PathBuf>,
+    /// Save every n generations in addition to the end of the run
+    pub every: Option<usize>,
+}
+
+impl Checkpoint {
+    /// Resumes the saved population or starts a new one.
+    pub fn population<B: Brain>(
+        &self,
+        config: PopulationConfig,
+        snake_config: &Arc<SnakeConfig>,
+    ) -> io::Result<Population<Snake<B>>> {
+        let path = match &self.load {
+            Some(path) => path,
+            None => return Ok(Population::new(config, snake_config)),
+        };
+        let failed = |error: io::Error| {
+            io::Error::new(
+                error.kind(),
+                format!("Failed to load {}: {}", path.display(), error),
+            )
+        };
+
+        let mut population: Population<Snake<B>> =
+            Population::load(path, config, snake_config).map_err(failed)?;
+        // The network has one input per sensor value, so the saved snakes must have been trained with the same sensors and topology
+        for snake in population.get_genomes() {
+            snake_config
+                .check_brain(snake.get_genotype())
+                .map_err(|error| failed(invalid_data(error)))?;
+        }
+        Ok(population)
+    }
+
+    /// Saves the population if a save path is configured.
+    pub fn save<B: Brain>(&self, population: &Population<Snake<B>>) {
+        if let Some(path) = &self.save {
+            if let Err(error) = population.save(path) {
+                eprintln!("Failed to save {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    /// Saves the population if it reached a multiple of `every` generations.
+    pub fn after_generation<B: Brain>(&self, population: &Population<Snake<B>>) {
+        if self
+            .every
+            .is_some_and(|every| every > 0 && population.generation.is_multiple_of(every))
+        {
+            self.save(population);
+        }
+    }
+}
+
+/// Loads a network saved on its own with `--best`.
+pub fn load_brain<B: Brain>(path: &Path, snake_config: &SnakeConfig) -> io::Result<B> {
+    let brain = B::load(path)?;
+    snake_config.check_brain(&brain).map_err(invalid_data)?;
+    Ok(brain)
+}
@@ -0,0 +1,755 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use genetic::crossover::{
+    BitSplice, Blend, CrossoverOperator, GeneGuard, LayerSwap, Points, Sbx, Uniform,
+};
+use genetic::evaluation::Aggregation;
+use genetic::genome::{Activation, Topology};
+use genetic::mutation::{Creep, Gaussian, MutationOperator, SelfAdaptiveGaussian, UniformReset};
+use genetic::mutation_rate::{
+    Decay, DiversityBurst, MutationRateController, OneFifth, ProgressStep, Schedule, SelfAdaptive,
+};
+use genetic::population::{Elitism, PopulationConfig, Replacement};
+use genetic::selection::{
+    Rank, Roulette, SelectionStrategy, StochasticUniversalSampling, Tournament, Truncation,
+};
+use genetic::speciation::Speciation;
+use snake::{Fitness, FitnessTerm, Point, Sensor, SnakeConfig, SpawnPolicy};
+
+use crate::checkpoint::Checkpoint;
+
+pub const USAGE: &str = "\
+Usage:
+    snake-ai-v2 [watch] [OPTIONS]    Train in the terminal UI
+                                     Space pauses, n steps, + and - change the speed, r toggles rendering,
+                                     [ ] halve and double the lower mutation rate bound, { } the upper one
+    snake-ai-v2 train [OPTIONS]      Train headless and print a summary per generation
+    snake-ai-v2 replay <PATH>        Play back a game saved with --replays
+                                     Space pauses, the arrow keys step while paused, Home and End seek
+    snake-ai-v2 play [OPTIONS]       Watch a network saved with --best play a new game, or play it yourself
+                                     The arrow keys steer, Enter starts a new game, Esc or q quits
+    snake-ai-v2 help                 Print this message
+
+Options:
+    --population <N>        Number of snakes per generation (default: 2000)
+    --mutation-rate <R>     Mutation rate of the first generation (default: 0.01)
+    --mutation-min <R>      Lower bound of the mutation rate (default: 0.00005)
+    --mutation-max <R>      Upper bound of the mutation rate (default: 0.05)
+    --mutation-control <C>  How the mutation rate changes between generations (default: progress:0.1)
+                              progress:<F>          F less after a new best fitness, F more otherwise
+                              one-fifth[:<C>]       divided by C while more than a fifth of the children beat
+                                                    their parents, multiplied by C otherwise (default: 0.85)
+                              linear:<R>,<G>        from --mutation-rate to R in G generations, by equal steps
+                              exponential:<R>,<G>   from --mutation-rate to R in G generations, by equal ratios
+                              burst:<D>[,<F>]       times F whenever the diversity falls below D (default: 10),
+                                                    progress:0.1 otherwise
+                              self-adaptive[:<T>]   every snake carries its own rate, children inherit the mean rate
+                                                    of their parents and change it with learning rate T (default: 0.2)
+    --mutation <OPERATOR>   How a mutated weight changes (default: uniform:3)
+                              uniform:<R>                 a new value in -R..R
+                              gaussian:<SIGMA>            add normal noise
+                              self-adaptive:<SIGMA>[,<T>] add normal noise with a step size per weight,
+                                                          which evolves with learning rate T (default: 0.2)
+                              creep:<STEP>                add a uniform value in -STEP..STEP
+    --episodes <N>          Games every snake plays per generation (default: 1)
+    --episode-seed <N>      Seed the games the same way for every snake and generation
+    --aggregate <A>         How the scores of the games add up to the fitness (default: mean)
+                              mean, median or min
+    --elite <N>             Best snakes that move on unchanged, a count or a percentage like 5% (default: 0)
+    --replacement <POLICY>  Who makes room for the children (default: generational)
+                              generational     everyone but the elites
+                              steady-state:<N> the N worst snakes
+                              mu+lambda:<MU>   everyone but the MU best, who are also the only parents
+                              mu,lambda:<MU>   everyone, only the MU best are parents
+    --selection <STRATEGY>  How the parents are picked (default: truncation:0.1)
+                              truncation:<F> the best fraction F, better ones more often
+                              tournament:<K> the best of K random snakes
+                              roulette       proportional to the fitness
+                              sus            stochastic universal sampling, roulette with evenly spaced pointers
+                              rank:<P>       by position only, the best P times as often as the average (1 to 2)
+    --crossover <OPERATOR>  How the weights of two parents are combined (default: bits)
+                              bits           splice the bit patterns of both weights
+                              uniform        every weight from a random parent
+                              points:<N>     cut at N random positions per layer and alternate the parents
+                              blend:<ALPHA>  BLX-alpha, a random value around both weights
+                              sbx:<ETA>      simulated binary crossover
+                              layer          every layer from a random parent
+    --gene-guard <GUARD>    What happens to weights that are infinite or NaN (default: reject)
+                              reject         keep the weight of the first parent
+                              repair:<LIMIT> clamp all weights into -LIMIT..LIMIT, NaN becomes 0
+    --species <D>[,<G>]     Split the snakes into species of networks closer than D (see diversity),
+                            which share their fitness and breed among themselves. A species that did not
+                            improve for G generations (default: 15) gets no more children
+    --seed <N>              Master seed, the same seed and options give identical runs
+    --load <PATH>           Resume the population saved in PATH
+    --save <PATH>           Save the population to PATH when the run ends
+    --checkpoint-every <N>  Also save every N generations
+    --stats <PATH>          Log the statistics of every generation to PATH, a .csv or .jsonl file
+    --replays <DIR>         Save the game of every snake that breaks the fitness record into DIR,
+                            its last game if it plays several episodes
+    --best <PATH>           Save the network of every snake that breaks the fitness record to PATH
+
+Game options:
+    --width <N>             Width of the board (default: 32)
+    --height <N>            Height of the board (default: 32)
+    --no-self-collision     Snakes survive running into their own body
+    --starvation-moves <N>  Moves a snake survives without eating (default: 100)
+    --apples <POLICY>       Where apples appear (default: random)
+                              random          a random free cell
+                              seeded:<N>      a random free cell, the same sequence for every snake
+                              fixed:<X,Y;..>  the given cells in order
+    --apple-count <N>       Number of apples on the field at once (default: 1)
+    --sensors <LIST>        Comma separated sensors of the snakes (default: legacy)
+                              legacy   distances to the walls and offset to the apple
+                              rays     distance to wall, body and apple in 8 directions
+                              heading  the direction the snake moves in
+                              tail     the direction the tail moves in
+    --normalise             Scale the sensor values independently of the board size
+    --fitness <LIST>        Comma separated terms the fitness adds up, each with an optional weight
+                            like wall:-5 (default: length)
+                              length           the length of the snake
+                              apples           apples eaten
+                              steps            moves survived
+                              steps-per-apple  moves per eaten apple
+                              wall             1 if the snake ran into a wall
+                              exp-length       2^apples
+
+Network options:
+    --brain <KIND>          How the network evolves (default: layers)
+                              layers  the weights of a fixed stack of layers
+                              neat    weights and shape after NEAT, starting without hidden neurons,
+                                      --activation picks the activation of the neurons it grows
+    --hidden <LIST>         Comma separated sizes of the hidden layers, or none (default: 4)
+    --activation <LIST>     Activation of each hidden layer, one for all or one per layer (default: relu)
+                              none, relu, tanh or sigmoid
+    --output-activation <A> Activation of the output layer (default: none)
+
+Train options:
+    --generations <N>       Stop after N generations (counted from --load)
+    --time-budget <T>       Stop after T of wall-clock time, e.g. 90, 90s, 15m or 2h
+
+Play options:
+    --genome <PATH>         The network that plays, without it you steer with the arrow keys
+                            The game and network options must match the ones it was trained with
+    --fps <N>               Moves per second (default: 10)
+    --seed <N>              Seed of the game
+";
+
+/// Which [`genetic::brain::Brain`] the snakes use.
+#[derive(Clone, Copy, Default)]
+pub enum BrainKind {
+    #[default]
+    Layers,
+    Neat,
+}
+
+pub enum Command {
+    Watch(WatchOptions),
+    Train(TrainOptions),
+    Replay(ReplayOptions),
+    Play(PlayOptions),
+    Help,
+}
+
+pub struct WatchOptions {
+    pub brain: BrainKind,
+    pub population: PopulationConfig,
+    pub snake: SnakeConfig,
+    pub checkpoint: Checkpoint,
+    /// Where the statistics of every generation are logged
+    pub stats: Option<PathBuf>,
+    /// Where record-breaking games are saved
+    pub replays: Option<PathBuf>,
+    /// Where the network of the record holder is saved
+    pub best: Option<PathBuf>,
+}
+
+pub struct TrainOptions {
+    pub brain: BrainKind,
+    pub population: PopulationConfig,
+    pub snake: SnakeConfig,
+    pub checkpoint: Checkpoint,
+    pub stats: Option<PathBuf>,
+    pub replays: Option<PathBuf>,
+    pub best: Option<PathBuf>,
+    pub generations: Option<usize>,
+    pub time_budget: Option<Duration>,
+}
+
+pub struct ReplayOptions {
+    pub path: PathBuf,
+}
+
+pub struct PlayOptions {
+    pub brain: BrainKind,
+    pub snake: SnakeConfig,
+    /// The network that plays, a human plays if there is none
+    pub genome: Option<PathBuf>,
+    /// Moves per second
+    pub fps: u32,
+    pub seed: Option<u64>,
+}
+
+impl Command {
+    /// Parses the command line arguments (without the program name).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+        let mut args = args.into_iter().peekable();
+
+        let subcommand = match args.peek().map(String::as_str) {
+            Some("watch") | Some("train") | Some("play") | Some("help") => args.next(),
+            Some("replay") => {
+                args.next();
+                let path = args.next().ok_or("Missing the path of the replay")?;
+                return match args.next() {
+                    Some(argument) => Err(format!("Unexpected argument {}", argument)),
+                    None => Ok(Command::Replay(ReplayOptions {
+                        path: PathBuf::from(path),
+                    })),
+                };
+            }
+            _ => None,
+        };
+
+        let mut population = PopulationConfig::default();
+        let mut checkpoint = Checkpoint::default();
+        let mut snake = SnakeConfig::default();
+        let mut hidden_sizes = None;
+        let mut hidden_activations = None;
+        let mut stats = None;
+        let mut replays = None;
+        let mut best = None;
+        let mut brain = BrainKind::default();
+        let mut genome = None;
+        let mut fps = None;
+        let mut generations = None;
+        let mut time_budget = None;
+        // The first flag that only matters for training, play rejects it
+        let mut training_flag = None;
+
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", flag))
+            };
+            match flag.as_str() {
+                "--population" => population.capacity = parse(&flag, &value()?)?,
+                "--mutation-rate" => population.mutation_rate = parse(&flag, &value()?)?,
+                "--mutation-min" => population.min_mutation_rate = parse(&flag, &value()?)?,
+                "--mutation-max" => population.max_mutation_rate = parse(&flag, &value()?)?,
+                "--mutation-control" => {
+                    population.mutation_control = parse_mutation_control(&value()?)?
+                }
+                "--mutation" => population.mutation = parse_mutation(&value()?)?,
+                "--episodes" => population.evaluation.episodes = parse(&flag, &value()?)?,
+                "--episode-seed" => {
+                    population.evaluation.shared_seed = Some(parse(&flag, &value()?)?)
+                }
+                "--aggregate" => population.evaluation.aggregation = parse_aggregation(&value()?)?,
+                "--elite" => population.elitism = parse_elitism(&value()?)?,
+                "--replacement" => population.replacement = parse_replacement(&value()?)?,
+                "--selection" => population.selection = parse_selection(&value()?)?,
+                "--crossover" => population.crossover = parse_crossover(&value()?)?,
+                "--gene-guard" => population.gene_guard = parse_gene_guard(&value()?)?,
+                "--species" => population.speciation = Some(parse_speciation(&value()?)?),
+                "--seed" => population.seed = Some(parse(&flag, &value()?)?),
+                "--load" => checkpoint.load = Some(PathBuf::from(value()?)),
+                "--save" => checkpoint.save = Some(PathBuf::from(value()?)),
+                "--checkpoint-every" => checkpoint.every = Some(parse(&flag, &value()?)?),
+                "--stats" => stats = Some(PathBuf::from(value()?)),
+                "--replays" => replays = Some(PathBuf::from(value()?)),
+                "--best" => best = Some(PathBuf::from(value()?)),
+                "--width" => snake.board.width = parse(&flag, &value()?)?,
+                "--height" => snake.board.height = parse(&flag, &value()?)?,
+                "--no-self-collision" => snake.rules.self_collision = false,
+                "--starvation-moves" => snake.rules.starvation_moves = parse(&flag, &value()?)?,
+                "--apples" => snake.spawn_policy = parse_spawn_policy(&value()?)?,
+                "--apple-count" => snake.apple_count = parse(&flag, &value()?)?,
+                "--sensors" => snake.sensors.sensors = parse_sensors(&value()?)?,
+                "--normalise" => snake.sensors.normalise = true,
+                "--fitness" => snake.fitness = parse_fitness(&value()?)?,
+                "--brain" => brain = parse_brain(&value()?)?,
+                "--hidden" => hidden_sizes = Some(parse_hidden_sizes(&value()?)?),
+                "--activation" => {
+                    hidden_activations =
+                        Some(value()?.split(',').map(parse_activation).collect::<Result<
+                            Vec<Activation>,
+                            String,
+                        >>(
+                        )?)
+                }
+                "--output-activation" => {
+                    snake.topology.output_activation = parse_activation(&value()?)?
+                }
+                "--generations" => generations = Some(parse(&flag, &value()?)?),
+                "--time-budget" => time_budget = Some(parse_duration(&value()?)?),
+                "--genome" => genome = Some(PathBuf::from(value()?)),
+                "--fps" => fps = Some(parse(&flag, &value()?)?),
+                "-h" | "--help" => return Ok(Command::Help),
+                _ => return Err(format!("Unknown argument {}", flag)),
+            }
+            let game_or_network = matches!(
+                flag.as_str(),
+                "--width"
+                    | "--height"
+                    | "--no-self-collision"
+                    | "--starvation-moves"
+                    | "--apples"
+                    | "--apple-count"
+                    | "--sensors"
+                    | "--normalise"
+                    | "--fitness"
+                    | "--brain"
+                    | "--hidden"
+                    | "--activation"
+                    | "--output-activation"
+            );
+            if !game_or_network && !matches!(flag.as_str(), "--genome" | "--fps" | "--seed") {
+                training_flag.get_or_insert(flag);
+            }
+        }
+
+        // The default selection picks its parents from the best tenth of the population
+        if population.capacity < 10 {
+            return Err(String::from("--population must be at least 10"));
+        }
+        if population.evaluation.episodes == 0 {
+            return Err(String::from("--episodes must be at least 1"));
+        }
+        if population.min_mutation_rate > population.max_mutation_rate {
+            return Err(String::from(
+                "--mutation-min must not exceed --mutation-max",
+            ));
+        }
+        let rates = [
+            population.mutation_rate,
+            population.min_mutation_rate,
+            population.max_mutation_rate,
+        ];
+        if !rates.iter().all(|rate| (0.0..=1.0).contains(rate)) {
+            return Err(String::from("Mutation rates must lie between 0 and 1"));
+        }
+        snake.topology.hidden = build_hidden_layers(hidden_sizes, hidden_activations)?;
+        if snake.apple_count == 0 {
+            return Err(String::from("--apple-count must be at least 1"));
+        }
+        if !(2..=1000).contains(&snake.board.width) || !(2..=1000).contains(&snake.board.height) {
+            return Err(String::from(
+                "--width and --height must lie between 2 and 1000",
+            ));
+        }
+        if let SpawnPolicy::Fixed(cells) = &snake.spawn_policy {
+            if let Some(cell) = cells.iter().find(|cell| !snake.board.contains(**cell)) {
+                return Err(format!(
+                    "Apple {},{} lies outside the field",
+                    cell.x, cell.y
+                ));
+            }
+        }
+        match population.replacement {
+            Replacement::SteadyState {
+                replacements: count,
+            }
+            | Replacement::MuPlusLambda { mu: count }
+            | Replacement::MuCommaLambda { mu: count }
+                if count >= population.capacity =>
+            {
+                return Err(String::from(
+                    "--replacement needs a number below --population",
+                ))
+            }
+            _ => {}
+        }
+        // Elites that fill the whole population leave no room for children, the evolution would stand still
+        if population.elitism.count(population.capacity) >= population.capacity {
+            return Err(String::from("--elite needs fewer snakes than --population"));
+        }
+        snake.record = replays.is_some();
+        if checkpoint.every.is_some() && checkpoint.save.is_none() {
+            return Err(String::from("--checkpoint-every requires --save"));
+        }
+
+        if fps == Some(0) {
+            return Err(String::from("--fps must be at least 1"));
+        }
+
+        match subcommand.as_deref() {
+            Some("train") if genome.is_none() && fps.is_none() => {
+                Ok(Command::Train(TrainOptions {
+                    brain,
+                    population,
+                    snake,
+                    checkpoint,
+                    stats,
+                    replays,
+                    best,
+                    generations,
+                    time_budget,
+                }))
+            }
+            Some("play") => match training_flag {
+                Some(flag) => Err(format!("{} is not supported by play", flag)),
+                None => Ok(Command::Play(PlayOptions {
+                    brain,
+                    snake: SnakeConfig {
+                        record: false,
+                        ..snake
+                    },
+                    genome,
+                    fps: fps.unwrap_or(10),
+                    seed: population.seed,
+                })),
+            },
+            Some("help") => Ok(Command::Help),
+            _ if genome.is_some() || fps.is_some() => Err(String::from(
+                "--genome and --fps are only supported by play",
+            )),
+            _ if generations.is_some() || time_budget.is_some() => Err(String::from(
+                "--generations and --time-budget are only supported by train",
+            )),
+            _ => Ok(Command::Watch(WatchOptions {
+                brain,
+                population,
+                snake,
+                checkpoint,
+                stats,
+                replays,
+                best,
+            })),
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {} for {}", value, flag))
+}
+
+/// Parses durations like `90`, `90s`, `15m` or `2h`. Plain numbers are seconds.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.find(|c: char| c.is_alphabetic()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: f64 = parse("--time-budget", number)?;
+    let seconds = match unit {
+        "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(format!("Unknown time unit {} for --time-budget", unit)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|error| format!("{} for --time-budget", error))
+}
+
+/// Parses `random`, `seeded:<seed>` or `fixed:<x>,<y>;<x>,<y>;...`.
+fn parse_spawn_policy(value: &str) -> Result<SpawnPolicy, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    match name {
+        "random" => Ok(SpawnPolicy::Random),
+        "seeded" => Ok(SpawnPolicy::Seeded(parse("--apples seeded", argument)?)),
+        "fixed" => {
+            let cells = argument
+                .split(';')
+                .map(|cell| {
+                    let (x, y) = cell
+                        .split_once(',')
+                        .ok_or_else(|| format!("Invalid cell {} for --apples fixed", cell))?;
+                    Ok(Point {
+                        x: parse("--apples fixed", x)?,
+                        y: parse("--apples fixed", y)?,
+                    })
+                })
+                .collect::<Result<Vec<Point>, String>>()?;
+            Ok(SpawnPolicy::Fixed(cells.into()))
+        }
+        _ => Err(format!("Unknown apple policy {}", name)),
+    }
+}
+
+/// Parses `uniform:<range>`, `gaussian:<sigma>`, `self-adaptive:<sigma>[,<learning rate>]` or `creep:<step>`.
+fn parse_mutation(value: &str) -> Result<Arc<dyn MutationOperator>, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    let flag = format!("--mutation {}", name);
+    let positive = |value: &str| match parse::<f32>(&flag, value)? {
+        number if number > 0.0 => Ok(number),
+        _ => Err(format!("{} needs a positive value", flag)),
+    };
+    match name {
+        "uniform" => Ok(Arc::new(UniformReset {
+            range: positive(argument)?,
+        })),
+        "gaussian" => Ok(Arc::new(Gaussian {
+            sigma: positive(argument)?,
+        })),
+        "self-adaptive" => {
+            let (sigma, learning_rate) = argument.split_once(',').unwrap_or((argument, "0.2"));
+            Ok(Arc::new(SelfAdaptiveGaussian {
+                initial_sigma: positive(sigma)?,
+                learning_rate: positive(learning_rate)?,
+            }))
+        }
+        "creep" => Ok(Arc::new(Creep {
+            step: positive(argument)?,
+        })),
+        _ => Err(format!("Unknown mutation {}", name)),
+    }
+}
+
+/// Parses `progress:<factor>`, `one-fifth[:<factor>]`, `linear:<rate>,<generations>`, `exponential:<rate>,<generations>`,
+/// `burst:<diversity>[,<factor>]` or `self-adaptive[:<learning rate>]`.
+fn parse_mutation_control(value: &str) -> Result<Arc<dyn MutationRateController>, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    let flag = format!("--mutation-control {}", name);
+    let positive = |value: &str| match parse::<f64>(&flag, value)? {
+        number if number > 0.0 => Ok(number),
+        _ => Err(format!("{} needs a positive value", flag)),
+    };
+    let schedule = |decay| -> Result<Arc<dyn MutationRateController>, String> {
+        let (end, generations) = argument
+            .split_once(',')
+            .ok_or_else(|| format!("{} needs a rate and a number of generations", flag))?;
+        Ok(Arc::new(Schedule {
+            end: positive(end)?,
+            generations: parse(&flag, generations)?,
+            decay,
+        }))
+    };
+    match name {
+        "progress" => match parse::<f64>(&flag, argument)? {
+            factor if (0.0..1.0).contains(&factor) => Ok(Arc::new(ProgressStep { factor })),
+            _ => Err(format!("{} needs a factor between 0 and 1", flag)),
+        },
+        "one-fifth" => {
+            match parse::<f64>(
+                &flag,
+                if argument.is_empty() {
+                    "0.85"
+                } else {
+                    argument
+                },
+            )? {
+                factor if factor > 0.0 && factor < 1.0 => Ok(Arc::new(OneFifth { factor })),
+                _ => Err(format!("{} needs a factor between 0 and 1", flag)),
+            }
+        }
+        "linear" => schedule(Decay::Linear),
+        "exponential" => schedule(Decay::Exponential),
+        "burst" => {
+            let (threshold, factor) = argument.split_once(',').unwrap_or((argument, "10"));
+            Ok(Arc::new(DiversityBurst {
+                threshold: positive(threshold)? as f32,
+                factor: positive(factor)?,
+                inner: Arc::new(ProgressStep { factor: 0.1 }),
+            }))
+        }
+        "self-adaptive" => Ok(Arc::new(SelfAdaptive {
+            learning_rate: positive(if argument.is_empty() { "0.2" } else { argument })?,
+        })),
+        _ => Err(format!("Unknown mutation control {}", name)),
+    }
+}
+
+fn parse_brain(value: &str) -> Result<BrainKind, String> {
+    match value {
+        "layers" => Ok(BrainKind::Layers),
+        "neat" => Ok(BrainKind::Neat),
+        _ => Err(format!("Unknown brain {}", value)),
+    }
+}
+
+/// Parses `<threshold>[,<stagnation>]`.
+fn parse_speciation(value: &str) -> Result<Speciation, String> {
+    let (threshold, stagnation) = value.split_once(',').unwrap_or((value, "15"));
+    match parse::<f32>("--species", threshold)? {
+        threshold if threshold > 0.0 => Ok(Speciation {
+            threshold,
+            stagnation: parse("--species", stagnation)?,
+        }),
+        _ => Err(String::from("--species needs a positive distance")),
+    }
+}
+
+fn parse_aggregation(value: &str) -> Result<Aggregation, String> {
+    match value {
+        "mean" => Ok(Aggregation::Mean),
+        "median" => Ok(Aggregation::Median),
+        "min" => Ok(Aggregation::Min),
+        _ => Err(format!("Unknown aggregation {}", value)),
+    }
+}
+
+/// Parses a count like `20` or a percentage like `5%`.
+fn parse_elitism(value: &str) -> Result<Elitism, String> {
+    match value.strip_suffix('%') {
+        Some(percentage) => match parse::<f64>("--elite", percentage)? {
+            percentage if (0.0..=100.0).contains(&percentage) => {
+                Ok(Elitism::Fraction(percentage / 100.0))
+            }
+            _ => Err(String::from("--elite must lie between 0% and 100%")),
+        },
+        None => Ok(Elitism::Count(parse("--elite", value)?)),
+    }
+}
+
+/// Parses `generational`, `steady-state:<replacements>`, `mu+lambda:<mu>` or `mu,lambda:<mu>`.
+fn parse_replacement(value: &str) -> Result<Replacement, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    let flag = format!("--replacement {}", name);
+    let positive = |value: &str| match parse(&flag, value)? {
+        0 => Err(format!("{} must be at least 1", flag)),
+        number => Ok(number),
+    };
+    match name {
+        "generational" => Ok(Replacement::Generational),
+        "steady-state" => Ok(Replacement::SteadyState {
+            replacements: positive(argument)?,
+        }),
+        "mu+lambda" => Ok(Replacement::MuPlusLambda {
+            mu: positive(argument)?,
+        }),
+        "mu,lambda" => Ok(Replacement::MuCommaLambda {
+            mu: positive(argument)?,
+        }),
+        _ => Err(format!("Unknown replacement {}", name)),
+    }
+}
+
+/// Parses `truncation:<fraction>`, `tournament:<size>`, `roulette`, `sus` or `rank:<pressure>`.
+fn parse_selection(value: &str) -> Result<Arc<dyn SelectionStrategy>, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    let flag = format!("--selection {}", name);
+    match name {
+        "truncation" => match parse(&flag, argument)? {
+            fraction if fraction > 0.0 && fraction <= 1.0 => Ok(Arc::new(Truncation { fraction })),
+            _ => Err(format!("{} must lie between 0 and 1", flag)),
+        },
+        "tournament" => match parse(&flag, argument)? {
+            0 => Err(format!("{} needs at least one contestant", flag)),
+            size => Ok(Arc::new(Tournament { size })),
+        },
+        "roulette" => Ok(Arc::new(Roulette)),
+        "sus" => Ok(Arc::new(StochasticUniversalSampling)),
+        "rank" => match parse(&flag, argument)? {
+            pressure if (1.0..=2.0).contains(&pressure) => Ok(Arc::new(Rank { pressure })),
+            _ => Err(format!("{} must lie between 1 and 2", flag)),
+        },
+        _ => Err(format!("Unknown selection {}", name)),
+    }
+}
+
+/// Parses `bits`, `uniform`, `points:<n>`, `blend:<alpha>`, `sbx:<eta>` or `layer`.
+fn parse_crossover(value: &str) -> Result<Arc<dyn CrossoverOperator>, String> {
+    let (name, argument) = value.split_once(':').unwrap_or((value, ""));
+    let flag = format!("--crossover {}", name);
+    match name {
+        "bits" => Ok(Arc::new(BitSplice)),
+        "uniform" => Ok(Arc::new(Uniform)),
+        "points" => match parse(&flag, argument)? {
+            0 => Err(format!("{} needs at least one point", flag)),
+            points => Ok(Arc::new(Points { points })),
+        },
+        "blend" => match parse(&flag, argument)? {
+            alpha if alpha >= 0.0 && f32::is_finite(alpha) => Ok(Arc::new(Blend { alpha })),
+            _ => Err(format!("{} must be finite and not negative", flag)),
+        },
+        "sbx" => match parse(&flag, argument)? {
+            eta if eta >= 0.0 => Ok(Arc::new(Sbx { eta })),
+            _ => Err(format!("{} must not be negative", flag)),
+        },
+        "layer" => Ok(Arc::new(LayerSwap)),
+        _ => Err(format!("Unknown crossover {}", name)),
+    }
+}
+
+/// Parses `reject` or `repair:<limit>`.
+fn parse_gene_guard(value: &str) -> Result<GeneGuard, String> {
+    match value.split_once(':').unwrap_or((value, "")) {
+        ("reject", _) => Ok(GeneGuard::Reject),
+        ("repair", limit) => match parse("--gene-guard repair", limit)? {
+            limit if limit > 0.0 => Ok(GeneGuard::Repair { limit }),
+            _ => Err(String::from("--gene-guard repair needs a positive limit")),
+        },
+        (name, _) => Err(format!("Unknown gene guard {}", name)),
+    }
+}
+
+fn parse_sensors(value: &str) -> Result<Vec<Sensor>, String> {
+    value
+        .split(',')
+        .map(|name| match name {
+            "legacy" => Ok(Sensor::Legacy),
+            "rays" => Ok(Sensor::Rays),
+            "heading" => Ok(Sensor::Heading),
+            "tail" => Ok(Sensor::TailDirection),
+            _ => Err(format!("Unknown sensor {}", name)),
+        })
+        .collect()
+}
+
+/// Parses a list like `apples,steps:0.01,wall:-5`, the weight defaults to 1.
+fn parse_fitness(value: &str) -> Result<Fitness, String> {
+    let terms = value
+        .split(',')
+        .map(|entry| {
+            let (name, weight) = entry.split_once(':').unwrap_or((entry, "1"));
+            let term = match name {
+                "length" => FitnessTerm::Length,
+                "apples" => FitnessTerm::Apples,
+                "steps" => FitnessTerm::Steps,
+                "steps-per-apple" => FitnessTerm::StepsPerApple,
+                "wall" => FitnessTerm::WallDeath,
+                "exp-length" => FitnessTerm::ExponentialLength,
+                _ => return Err(format!("Unknown fitness term {}", name)),
+            };
+            Ok((term, parse("--fitness", weight)?))
+        })
+        .collect::<Result<Vec<(FitnessTerm, f32)>, String>>()?;
+    Ok(Fitness { terms })
+}
+
+fn parse_hidden_sizes(value: &str) -> Result<Vec<usize>, String> {
+    if value == "none" {
+        return Ok(vec![]);
+    }
+    value
+        .split(',')
+        .map(|size| match parse("--hidden", size)? {
+            0 => Err(String::from("Hidden layers need at least one neuron")),
+            size => Ok(size),
+        })
+        .collect()
+}
+
+fn parse_activation(value: &str) -> Result<Activation, String> {
+    match value {
+        "none" => Ok(Activation::None),
+        "relu" => Ok(Activation::ReLU),
+        "tanh" => Ok(Activation::Tanh),
+        "sigmoid" => Ok(Activation::Sigmoid),
+        _ => Err(format!("Unknown activation {}", value)),
+    }
+}
+
+/// Pairs the sizes of `--hidden` with the activations of `--activation`, falling back to the default topology.
+fn build_hidden_layers(
+    sizes: Option<Vec<usize>>,
+    activations: Option<Vec<Activation>>,
+) -> Result<Vec<(usize, Activation)>, String> {
+    let default = Topology::default().hidden;
+    let sizes = sizes.unwrap_or_else(|| default.iter().map(|(size, _)| *size).collect());
+    let activations = activations.unwrap_or_else(|| vec![default[0].1]);
+    match activations.len() {
+        1 => Ok(sizes
+            .into_iter()
+            .map(|size| (size, activations[0]))
+            .collect()),
+        count if count == sizes.len() => Ok(sizes.into_iter().zip(activations).collect()),
+        count => Err(format!(
+            "--activation has {} entries, expected 1 or {}",
+            count,
+            sizes.len()
+        )),
+    }
+}
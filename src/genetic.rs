@@ -1,5 +0,0 @@
-pub mod algorithms;
-pub mod genome;
-pub mod organism;
-pub mod population;
-pub mod traits;
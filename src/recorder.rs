@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+
+use genetic::brain::Brain;
+use genetic::organism::Organism;
+use genetic::population::Population;
+use snake::Snake;
+
+/// Saves the game and the network of the best snake of a generation whenever it beats every snake before it.
+pub struct Recorder {
+    directory: Option<PathBuf>,
+    /// Where the network of the record holder is saved, overwritten by every new record
+    best: Option<PathBuf>,
+    record: f32,
+}
+
+impl Recorder {
+    /// Creates a recorder that saves the games into `directory` and the network into `best`, or does nothing if there is neither.
+    ///
+    /// The snakes only record their games if [`snake::SnakeConfig::record`] is set.
+    pub fn new(directory: Option<PathBuf>, best: Option<PathBuf>) -> Self {
+        Recorder {
+            directory,
+            best,
+            record: f32::NEG_INFINITY,
+        }
+    }
+
+    /// Saves the replay and the network of the best snake if it broke the record, call it before the evolution.
+    pub fn after_generation<B: Brain>(&mut self, population: &mut Population<Snake<B>>) {
+        if self.directory.is_none() && self.best.is_none() {
+            return;
+        }
+        let fitnesses = population.fitnesses();
+        let best = fitnesses
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        let (index, fitness) = match best {
+            Some((index, fitness)) if *fitness > self.record => (index, *fitness),
+            _ => return,
+        };
+        self.record = fitness;
+        let generation = population.generation;
+        let snake = &population.get_genomes()[index];
+
+        if let Some(path) = &self.best {
+            if let Err(error) = snake.get_genotype().save(path) {
+                eprintln!("Failed to save {}: {}", path.display(), error);
+            }
+        }
+
+        let directory = match &self.directory {
+            Some(directory) => directory,
+            None => return,
+        };
+        // With several episodes this is the last game of the snake, so the file is named after the score of that game
+        let replay = match snake.game().get_replay() {
+            Some(replay) => replay,
+            None => return,
+        };
+        let path = directory.join(format!(
+            "generation-{}-fitness-{}.replay",
+            generation,
+            snake.get_fitness()
+        ));
+        if let Err(error) = fs::create_dir_all(directory).and_then(|_| replay.save(&path)) {
+            eprintln!("Failed to save {}: {}", path.display(), error);
+        }
+    }
+}
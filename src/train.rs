@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use genetic::brain::Brain;
+use genetic::stats::StatsLog;
+use snake::DeathCause;
+
+use crate::cli::TrainOptions;
+use crate::recorder::Recorder;
+
+/// Runs the evolution without a terminal UI as fast as possible.
+pub fn run<B: Brain>(options: TrainOptions) {
+    let snake_config = Arc::new(options.snake.clone());
+    let mut population = match options
+        .checkpoint
+        .population::<B>(options.population.clone(), &snake_config)
+    {
+        Ok(population) => population,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+    let mut stats_log = match options.stats.as_deref().map(StatsLog::create).transpose() {
+        Ok(stats_log) => stats_log,
+        Err(error) => {
+            eprintln!("Failed to create the statistics log: {}", error);
+            std::process::exit(1);
+        }
+    };
+    let mut recorder = Recorder::new(options.replays.clone(), options.best.clone());
+    let started = Instant::now();
+    // A resumed population counts its generations from where it was saved
+    let first_generation = population.generation;
+
+    println!(
+        "training {} snakes, generations: {}, time budget: {}, seed: {}",
+        population.get_capacity(),
+        options
+            .generations
+            .map_or(String::from("unlimited"), |generations| generations
+                .to_string()),
+        options
+            .time_budget
+            .map_or(String::from("unlimited"), |budget| format!("{:?}", budget)),
+        options
+            .population
+            .seed
+            .map_or(String::from("random"), |seed| seed.to_string()),
+    );
+
+    loop {
+        let out_of_time = options
+            .time_budget
+            .is_some_and(|budget| started.elapsed() >= budget);
+        let out_of_generations = options
+            .generations
+            .is_some_and(|generations| population.generation - first_generation >= generations);
+        if out_of_time || out_of_generations {
+            break;
+        }
+
+        population.tick();
+
+        if population.is_dead() {
+            let stats = population.stats();
+            let wins = population
+                .get_genomes()
+                .iter()
+                .filter(|snake| snake.game().has_won())
+                .count();
+
+            println!(
+                "generation: {} snakes: {} max_fitness: {} mean_fitness: {:.3} mutation_rate: {:.6} diversity: {:.3e} species: {} deaths (wall/self/starvation): {}/{}/{} wins: {} elite_won: {} elapsed: {:.1}s",
+                stats.generation,
+                population.get_capacity(),
+                stats.max_fitness,
+                stats.mean_fitness,
+                stats.mutation_rate,
+                stats.diversity,
+                stats
+                    .species
+                    .map_or(String::from("-"), |species| species.to_string()),
+                stats.deaths_of(DeathCause::Wall.name()),
+                stats.deaths_of(DeathCause::SelfCollision.name()),
+                stats.deaths_of(DeathCause::Starvation.name()),
+                wins,
+                population
+                    .elite_won()
+                    .map_or("-", |won| if won { "yes" } else { "no" }),
+                started.elapsed().as_secs_f64(),
+            );
+            if let Some(log) = &mut stats_log {
+                if let Err(error) = log.write(&stats) {
+                    eprintln!("Failed to write the statistics: {}", error);
+                }
+            }
+
+            recorder.after_generation(&mut population);
+            population.evolution();
+            options.checkpoint.after_generation(&population);
+        }
+    }
+
+    options.checkpoint.save(&population);
+}
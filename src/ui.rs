@@ -0,0 +1,681 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use console_engine::{pixel, Color, ConsoleEngine, KeyCode};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use genetic::brain::Brain;
+use genetic::environment::{Environment, Policy};
+use genetic::organism::Organism;
+use genetic::population::Population;
+use genetic::stats::StatsLog;
+use snake::{Board, Direction, Point, Replay, Snake, SnakeGame, DIRECTIONS};
+
+use crate::checkpoint::load_brain;
+use crate::checkpoint::Checkpoint;
+use crate::cli::{PlayOptions, ReplayOptions, WatchOptions};
+use crate::recorder::Recorder;
+
+fn draw_borders(canvas: &mut ConsoleEngine, board: &Board, shift: Point) {
+    let border_color = Color::DarkRed;
+    let border_pixel = pixel::pxl_bg(' ', border_color);
+
+    canvas.set_pxl(shift.x, shift.y, border_pixel);
+    canvas.set_pxl(board.width + 1 + shift.x, shift.y, border_pixel);
+    canvas.set_pxl(
+        board.width + 1 + shift.x,
+        board.height + 1 + shift.y,
+        border_pixel,
+    );
+    canvas.set_pxl(shift.x, board.height + 1 + shift.y, border_pixel);
+
+    for x in 0..board.width + 1 {
+        canvas.set_pxl(x + shift.x, shift.y, border_pixel);
+        canvas.set_pxl(x + shift.x, board.height + 1 + shift.y, border_pixel);
+    }
+    for y in 0..board.height + 1 {
+        canvas.set_pxl(shift.x, y + shift.y, border_pixel);
+        canvas.set_pxl(board.width + 1 + shift.x, y + shift.y, border_pixel);
+    }
+}
+
+/// Formats a best fitness, `-` while there is none yet (negative infinity).
+fn describe_fitness(fitness: f32) -> String {
+    if fitness.is_finite() {
+        fitness.to_string()
+    } else {
+        String::from("-")
+    }
+}
+
+/// Which snakes the board shows.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    /// Every living snake on top of each other
+    All,
+    /// The living snake with the highest fitness
+    Best,
+    /// The snake at this index of the population, even after it died
+    Single(usize),
+}
+
+impl View {
+    /// The index of the snake to focus on, `None` to draw all of them
+    fn focus<B: Brain>(self, snakes: &[Snake<B>]) -> Option<usize> {
+        match self {
+            View::All => None,
+            View::Best => snakes
+                .iter()
+                .enumerate()
+                .filter(|(_, snake)| snake.is_alive())
+                .max_by(|(_, a), (_, b)| a.get_fitness().total_cmp(&b.get_fitness()))
+                .map(|(index, _)| index),
+            View::Single(index) => Some(index.min(snakes.len() - 1)),
+        }
+    }
+
+    /// Focuses the next (`step` 1) or previous (`step` -1) living snake after the current focus.
+    fn cycle<B: Brain>(self, snakes: &[Snake<B>], step: isize) -> View {
+        let count = snakes.len() as isize;
+        let start = self.focus(snakes).unwrap_or(0) as isize;
+        (1..=count)
+            .map(|offset| (start + step * offset).rem_euclid(count) as usize)
+            .find(|index| snakes[*index].is_alive())
+            .map_or(self, View::Single)
+    }
+
+    fn name(self) -> String {
+        match self {
+            View::All => String::from("all"),
+            View::Best => String::from("best"),
+            View::Single(index) => format!("snake {}", index),
+        }
+    }
+}
+
+fn draw_game(canvas: &mut ConsoleEngine, game: &SnakeGame, board: &Board, shift: Point) {
+    for apple in game.get_apples() {
+        canvas.set_pxl(
+            apple.x + 1 + shift.x,
+            apple.y + 1 + shift.y,
+            pixel::pxl_bg(' ', Color::Red),
+        );
+    }
+    // The deadly move can leave the board, that head is not drawn
+    for cell in game
+        .get_cells()
+        .iter()
+        .filter(|cell| board.contains(**cell))
+    {
+        canvas.set_pxl(
+            cell.x + 1 + shift.x,
+            cell.y + 1 + shift.y,
+            pixel::pxl_bg(' ', Color::Green),
+        );
+    }
+}
+
+/// Shows what the focused snake perceives and how its network responds, `height` is the room left for it.
+fn draw_side_panel<B: Brain>(
+    canvas: &mut ConsoleEngine,
+    snake: &Snake<B>,
+    index: usize,
+    at: Point,
+    height: i32,
+) {
+    canvas.print(
+        at.x,
+        at.y,
+        format!(
+            "snake {} fitness: {} length: {}",
+            index,
+            snake.get_fitness(),
+            snake.game().get_length()
+        )
+        .as_str(),
+    );
+
+    canvas.print(at.x, at.y + 2, "outputs:");
+    for (row, (direction, output)) in DIRECTIONS.iter().zip(snake.get_outputs()).enumerate() {
+        canvas.print(
+            at.x,
+            at.y + 3 + row as i32,
+            format!("{:>6}: {:>10.3}", direction.name(), output).as_str(),
+        );
+    }
+
+    let sensors_top = at.y + 4 + DIRECTIONS.len() as i32;
+    let columns = 3;
+    canvas.print(at.x, sensors_top, "sensors:");
+    for (i, value) in snake.game().observe().iter().enumerate() {
+        let row = (i / columns) as i32;
+        if row + 1 >= height - (sensors_top - at.y) {
+            break;
+        }
+        canvas.print(
+            at.x + (i % columns) as i32 * 18,
+            sensors_top + 1 + row,
+            format!("{:>3}: {:>10.3}", i, value).as_str(),
+        );
+    }
+}
+
+/// How the simulation runs, changed with the keyboard while it runs.
+struct Controls {
+    paused: bool,
+    /// Ticks per frame as a power of two, negative values mean one tick every `2^-speed` frames
+    speed: i32,
+    /// Without rendering the board every frame ticks as long as the frame budget allows
+    rendering: bool,
+}
+
+impl Controls {
+    const MIN_SPEED: i32 = -9;
+    const MAX_SPEED: i32 = 10;
+    /// How long a frame ticks at most while the rendering is off
+    const UNRENDERED_FRAME: Duration = Duration::from_millis(50);
+
+    /// The number of ticks of the given frame
+    fn ticks(&self, frame: u64) -> u32 {
+        if self.speed >= 0 {
+            1 << self.speed
+        } else if frame.is_multiple_of(1 << -self.speed) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn describe_speed(&self) -> String {
+        if self.speed >= 0 {
+            format!("{}x", 1 << self.speed)
+        } else {
+            format!("1/{}x", 1 << -self.speed)
+        }
+    }
+}
+
+/// Ticks the population once and starts the next generation when every snake is dead.
+fn advance<B: Brain>(
+    population: &mut Population<Snake<B>>,
+    stats_log: &mut Option<StatsLog>,
+    recorder: &mut Recorder,
+    checkpoint: &Checkpoint,
+) {
+    population.tick();
+
+    if population.is_dead() {
+        if let Some(log) = stats_log {
+            if let Err(error) = log.write(&population.stats()) {
+                eprintln!("Failed to write the statistics: {}", error);
+            }
+        }
+        recorder.after_generation(population);
+        population.evolution();
+        checkpoint.after_generation(population);
+    }
+}
+
+/// Runs the evolution in the terminal.
+///
+/// Shows every living snake, only the best one (b) or a single one, cycling through them with the arrow keys.
+/// A single snake comes with a side panel of its sensors and network outputs. `a` goes back to all snakes.
+///
+/// Space pauses, `n` ticks once while paused, `+` and `-` change the speed and `r` turns the rendering off to train at full speed.
+/// `[` and `]` halve and double the lower bound of the mutation rate, `{` and `}` the upper one.
+pub fn run<B: Brain>(options: WatchOptions) {
+    let snake_config = Arc::new(options.snake);
+    let mut population = match options
+        .checkpoint
+        .population::<B>(options.population, &snake_config)
+    {
+        Ok(population) => population,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut stats_log = match options.stats.as_deref().map(StatsLog::create).transpose() {
+        Ok(stats_log) => stats_log,
+        Err(error) => {
+            eprintln!("Failed to create the statistics log: {}", error);
+            std::process::exit(1);
+        }
+    };
+
+    let mut recorder = Recorder::new(options.replays, options.best);
+    let mut view = View::All;
+    let mut controls = Controls {
+        paused: false,
+        speed: 0,
+        rendering: true,
+    };
+    let checkpoint = options.checkpoint;
+
+    let board = snake_config.board;
+    let status_bar_height = 10;
+    // console_engine only reads the keyboard while it waits for the next frame.
+    // With u32::MAX frames per second there was never time left, so no key press ever arrived.
+    // A millisecond per frame is the shortest wait it supports.
+    let mut engine = ConsoleEngine::init(
+        (board.width + 64) as u32,
+        (board.height + status_bar_height + 4) as u32,
+        1000,
+    )
+    .unwrap();
+
+    let mut frame: u64 = 0;
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+        frame += 1;
+
+        if engine.is_key_pressed(KeyCode::Esc) {
+            checkpoint.save(&population);
+            break;
+        }
+
+        if engine.is_key_pressed(KeyCode::Char('q')) {
+            population.kill();
+        }
+        if engine.is_key_pressed(KeyCode::Char('a')) {
+            view = View::All;
+        }
+        if engine.is_key_pressed(KeyCode::Char('b')) {
+            view = View::Best;
+        }
+        if engine.is_key_pressed(KeyCode::Right) {
+            view = view.cycle(population.get_genomes(), 1);
+        }
+        if engine.is_key_pressed(KeyCode::Left) {
+            view = view.cycle(population.get_genomes(), -1);
+        }
+        if engine.is_key_pressed(KeyCode::Char(' ')) {
+            controls.paused = !controls.paused;
+        }
+        if engine.is_key_pressed(KeyCode::Char('+')) {
+            controls.speed = (controls.speed + 1).min(Controls::MAX_SPEED);
+        }
+        if engine.is_key_pressed(KeyCode::Char('-')) {
+            controls.speed = (controls.speed - 1).max(Controls::MIN_SPEED);
+        }
+        if engine.is_key_pressed(KeyCode::Char('r')) {
+            controls.rendering = !controls.rendering;
+        }
+
+        let (mut min_mutation_rate, mut max_mutation_rate) = population.mutation_bounds();
+        if engine.is_key_pressed(KeyCode::Char('[')) {
+            min_mutation_rate /= 2.0;
+        }
+        if engine.is_key_pressed(KeyCode::Char(']')) {
+            min_mutation_rate = (min_mutation_rate * 2.0).min(1.0);
+            max_mutation_rate = max_mutation_rate.max(min_mutation_rate);
+        }
+        if engine.is_key_pressed(KeyCode::Char('{')) {
+            max_mutation_rate /= 2.0;
+            min_mutation_rate = min_mutation_rate.min(max_mutation_rate);
+        }
+        if engine.is_key_pressed(KeyCode::Char('}')) {
+            max_mutation_rate = (max_mutation_rate * 2.0).min(1.0);
+        }
+        population.set_mutation_bounds(min_mutation_rate, max_mutation_rate);
+
+        let work_started = Instant::now();
+        if controls.paused {
+            if engine.is_key_pressed(KeyCode::Char('n')) {
+                advance(&mut population, &mut stats_log, &mut recorder, &checkpoint);
+            }
+        } else if controls.rendering {
+            for _ in 0..controls.ticks(frame) {
+                advance(&mut population, &mut stats_log, &mut recorder, &checkpoint);
+            }
+        } else {
+            let started = Instant::now();
+            while started.elapsed() < Controls::UNRENDERED_FRAME {
+                advance(&mut population, &mut stats_log, &mut recorder, &checkpoint);
+            }
+        }
+        // A frame that took longer than its millisecond leaves wait_frame no time to read the keyboard.
+        // Waiting once more restarts the frame timer, pending key presses stay queued for the next frame.
+        if work_started.elapsed() >= Duration::from_millis(1) {
+            engine.wait_frame();
+        }
+
+        let shift = Point {
+            x: 1,
+            y: status_bar_height,
+        };
+
+        if controls.rendering {
+            draw_borders(&mut engine, &board, shift);
+
+            let snakes = population.get_genomes();
+            match view.focus(snakes) {
+                Some(index) => {
+                    draw_game(&mut engine, snakes[index].game(), &board, shift);
+                    draw_side_panel(
+                        &mut engine,
+                        &snakes[index],
+                        index,
+                        Point {
+                            x: board.width + 4,
+                            y: shift.y,
+                        },
+                        board.height + 2,
+                    );
+                }
+                None => snakes
+                    .iter()
+                    .filter(|snake| snake.is_alive())
+                    .for_each(|snake| draw_game(&mut engine, snake.game(), &board, shift)),
+            }
+        }
+
+        engine.print(
+            1,
+            0,
+            format!("snakes_alive: {}", population.alive_genomes_count,).as_str(),
+        );
+
+        engine.print(
+            1,
+            1,
+            format!(
+                "generation: {} episode: {} species: {}",
+                population.generation,
+                population.episode + 1,
+                population
+                    .species_count()
+                    .map_or(String::from("-"), |species| species.to_string())
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            2,
+            format!("mutation_rate: {}", population.mutation_rate).as_str(),
+        );
+        engine.print(
+            1,
+            3,
+            format!(
+                "max_fitness_current: {}",
+                describe_fitness(population.max_fitness_current)
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            4,
+            format!(
+                "max_fitness_prev: {}",
+                describe_fitness(population.max_fitness_prev)
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            5,
+            format!(
+                "elite_won_prev: {}",
+                population
+                    .elite_won_prev
+                    .map_or("-", |won| if won { "yes" } else { "no" })
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            6,
+            format!("view: {} (a: all, b: best, arrows: cycle)", view.name()).as_str(),
+        );
+        engine.print(
+            1,
+            7,
+            format!(
+                "{} speed: {} rendering: {} mutation_bounds: {}..{}",
+                if controls.paused { "paused" } else { "running" },
+                controls.describe_speed(),
+                if controls.rendering { "on" } else { "off" },
+                min_mutation_rate,
+                max_mutation_rate
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            8,
+            "space: pause, n: step, +/-: speed, r: rendering, [ ]: min rate, { }: max rate, esc: quit",
+        );
+
+        engine.draw();
+    }
+}
+
+/// Plays back a recorded game.
+///
+/// Space pauses, the arrow keys step back and forth while paused, Home and End jump to the start and the end.
+pub fn replay(options: ReplayOptions) {
+    let replay = match Replay::load(&options.path) {
+        Ok(replay) => replay,
+        Err(error) => {
+            eprintln!("Failed to load {}: {}", options.path.display(), error);
+            std::process::exit(1);
+        }
+    };
+    let frames = replay.frames();
+    let last = frames.len() - 1;
+
+    let board = replay.board;
+    let status_bar_height = 5;
+    let mut engine = ConsoleEngine::init(
+        (board.width + 64) as u32,
+        (board.height + status_bar_height + 4) as u32,
+        15,
+    )
+    .unwrap();
+
+    let mut step = 0;
+    let mut paused = false;
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        let shift = Point {
+            x: 1,
+            y: status_bar_height,
+        };
+        draw_borders(&mut engine, &board, shift);
+
+        let frame = &frames[step];
+        for apple in &frame.apples {
+            engine.set_pxl(
+                apple.x + 1 + shift.x,
+                apple.y + 1 + shift.y,
+                pixel::pxl_bg(' ', Color::Red),
+            );
+        }
+        // The deadly move can leave the board, that head is not drawn
+        for cell in frame.cells.iter().filter(|cell| board.contains(**cell)) {
+            engine.set_pxl(
+                cell.x + 1 + shift.x,
+                cell.y + 1 + shift.y,
+                pixel::pxl_bg(' ', Color::Green),
+            );
+        }
+
+        engine.print(
+            1,
+            0,
+            format!(
+                "step: {}/{}{}",
+                step,
+                last,
+                if paused { " (paused)" } else { "" }
+            )
+            .as_str(),
+        );
+        engine.print(1, 1, format!("length: {}", frame.cells.len()).as_str());
+        let outcome = match replay.death_cause {
+            Some(cause) => cause.name(),
+            None => "survived",
+        };
+        engine.print(
+            1,
+            2,
+            format!("outcome: {}", if step == last { outcome } else { "-" }).as_str(),
+        );
+
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('q')) {
+            break;
+        }
+        if engine.is_key_pressed(KeyCode::Char(' ')) {
+            paused = !paused;
+        }
+        if engine.is_key_pressed(KeyCode::Left) {
+            paused = true;
+            step = step.saturating_sub(1);
+        }
+        if engine.is_key_pressed(KeyCode::Right) {
+            paused = true;
+            step = (step + 1).min(last);
+        }
+        if engine.is_key_pressed(KeyCode::Home) {
+            step = 0;
+        }
+        if engine.is_key_pressed(KeyCode::End) {
+            step = last;
+        }
+        if !paused && step < last {
+            step += 1;
+        }
+
+        engine.draw();
+    }
+}
+
+/// A human player, who steers with the arrow keys.
+struct Keyboard {
+    /// The arrow key pressed in this frame
+    turn: Option<Direction>,
+}
+
+/// Without a key press the snake keeps its direction.
+impl Policy<SnakeGame> for Keyboard {
+    fn act(&self, game: &SnakeGame) -> Direction {
+        self.turn.unwrap_or(game.get_direction())
+    }
+}
+
+/// Lets a saved network play a new game at a speed a human can follow, or a human play by the same rules.
+///
+/// Without a network the arrow keys steer and the game starts with the first one.
+/// Enter starts a new game after the snake died, Esc or q quits.
+pub fn play<B: Brain>(options: PlayOptions) {
+    let snake_config = Arc::new(options.snake);
+    let mut rng = StdRng::seed_from_u64(options.seed.unwrap_or_else(rand::random));
+    let network = options
+        .genome
+        .as_ref()
+        .map(|path| match load_brain::<B>(path, &snake_config) {
+            Ok(genome) => genome,
+            Err(error) => {
+                eprintln!("Failed to load {}: {}", path.display(), error);
+                std::process::exit(1);
+            }
+        });
+    let human = network.is_none();
+    let mut game = SnakeGame::new(snake_config.clone(), rng.gen());
+
+    let board = snake_config.board;
+    let status_bar_height = 6;
+    let mut engine = ConsoleEngine::init(
+        (board.width + 64) as u32,
+        (board.height + status_bar_height + 4) as u32,
+        options.fps,
+    )
+    .unwrap();
+
+    let arrows = [
+        (KeyCode::Up, Direction::Up),
+        (KeyCode::Down, Direction::Down),
+        (KeyCode::Left, Direction::Left),
+        (KeyCode::Right, Direction::Right),
+    ];
+    let mut games = 1;
+    let mut best_fitness = f32::NEG_INFINITY;
+    // The human has not pressed an arrow key in this game yet
+    let mut waiting = human;
+    loop {
+        engine.wait_frame();
+        engine.clear_screen();
+
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('q')) {
+            break;
+        }
+        let turn = arrows
+            .iter()
+            .find_map(|(key, direction)| engine.is_key_pressed(*key).then_some(*direction));
+        waiting &= turn.is_none();
+
+        // Without a network the human is the policy
+        let keyboard = Keyboard { turn };
+        let player: &dyn Policy<SnakeGame> = match &network {
+            Some(network) => network,
+            None => &keyboard,
+        };
+        if game.is_alive() {
+            if !waiting && game.step(player.act(&game)).done {
+                best_fitness = best_fitness.max(game.get_fitness());
+            }
+        } else if engine.is_key_pressed(KeyCode::Enter) {
+            game.reset();
+            games += 1;
+            waiting = human;
+        }
+
+        let shift = Point {
+            x: 1,
+            y: status_bar_height,
+        };
+        draw_borders(&mut engine, &board, shift);
+        draw_game(&mut engine, &game, &board, shift);
+
+        engine.print(
+            1,
+            0,
+            format!(
+                "player: {} game: {}",
+                if human { "human" } else { "network" },
+                games
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            1,
+            format!(
+                "length: {} fitness: {}",
+                game.get_length(),
+                game.get_fitness()
+            )
+            .as_str(),
+        );
+        engine.print(
+            1,
+            2,
+            format!("best_fitness: {}", describe_fitness(best_fitness)).as_str(),
+        );
+        let outcome = match (game.is_alive(), game.get_death_cause()) {
+            (true, _) if waiting => "press an arrow key to start",
+            (true, _) => "-",
+            (false, Some(cause)) => cause.name(),
+            (false, None) => "won",
+        };
+        engine.print(1, 3, format!("outcome: {}", outcome).as_str());
+        engine.print(1, 4, "enter: new game, esc: quit");
+
+        engine.draw();
+    }
+}